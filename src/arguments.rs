@@ -0,0 +1,115 @@
+//! This module parses Funkfragenhelfer's command line arguments.
+//!
+//! Currently, the following arguments are supported:
+//! * --data-dir <DIR>: Use the given directory instead of the default
+//!   data dir (see the paths module)
+//! * --profile <NAME>: Start directly with the given profile instead of
+//!   showing the profile picker (see the profiles module)
+//! * --cli: Practice on the command line instead of in the GUI (see the
+//!   cli module)
+//! * --tui: Practice in the full-screen terminal UI instead of in the GUI
+//!   (see the tui module)
+//! * --seed <N>: Choose the questions, shuffle their answers and draw the exams
+//!   reproducibly with the given seed (see the session module)
+//! * --help: Print the usage and exit
+
+// CONSTANTS SECTION //
+/// The usage text printed with "--help" or after an invalid argument
+pub const USAGE: &str =
+    "Usage: funkfragenhelfer [--data-dir <DIR>] [--profile <NAME>] [--cli | --tui] [--seed <N>]
+
+Options:
+  --data-dir <DIR>  Store the configuration and the learning progress in DIR
+  --profile <NAME>  Learn with the profile NAME without showing the profile picker
+  --cli             Practice on the command line instead of in the GUI
+  --tui             Practice in the full-screen terminal UI instead of in the GUI
+  --seed <N>        Choose the questions and shuffle the answers reproducibly with
+                    the seed N (a non-negative integer)
+  --help            Print this help";
+
+// ENUM SECTION //
+/// The front ends with which Funkfragenhelfer can be used.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Frontend {
+    /// The graphical user interface (see the gui module)
+    #[default]
+    Gui,
+    /// The line-based command-line mode (see the cli module)
+    Cli,
+    /// The full-screen terminal UI (see the tui module)
+    Tui,
+}
+
+impl Frontend {
+    /// Returns whether or not the front end runs in a terminal, in which case
+    /// errors on startup are also handled in the terminal.
+    pub fn is_terminal(&self) -> bool {
+        *self != Frontend::Gui
+    }
+}
+
+// STRUCT SECTION //
+/// The parsed command line arguments.
+#[derive(Debug, Default)]
+pub struct Arguments {
+    /// The data dir override given with "--data-dir"
+    pub data_dir: Option<String>,
+    /// The profile given with "--profile"
+    pub profile: Option<String>,
+    /// The seed of the random number generators given with "--seed"
+    pub seed: Option<u64>,
+    /// The front end chosen with "--cli" or "--tui"
+    pub frontend: Frontend,
+    /// Whether or not "--help" was given
+    pub show_help: bool,
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Parses the given command line arguments (without the program name).
+///
+/// ### Return value
+/// * The parsed arguments, or an error message if an argument is unknown
+///   or lacks its value
+pub fn parse_arguments<I: Iterator<Item = String>>(mut args: I) -> Result<Arguments, String> {
+    let mut arguments = Arguments::default();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            arguments.show_help = true;
+        } else if arg == "--cli" {
+            arguments.frontend = Frontend::Cli;
+        } else if arg == "--tui" {
+            arguments.frontend = Frontend::Tui;
+        } else if arg == "--data-dir" {
+            match args.next() {
+                Some(data_dir) => arguments.data_dir = Some(data_dir),
+                None => return Err(String::from("Missing directory after --data-dir")),
+            }
+        } else if let Some(data_dir) = arg.strip_prefix("--data-dir=") {
+            arguments.data_dir = Some(data_dir.to_string());
+        } else if arg == "--profile" {
+            match args.next() {
+                Some(profile) => arguments.profile = Some(profile),
+                None => return Err(String::from("Missing profile name after --profile")),
+            }
+        } else if let Some(profile) = arg.strip_prefix("--profile=") {
+            arguments.profile = Some(profile.to_string());
+        } else if arg == "--seed" {
+            match args.next() {
+                Some(seed) => arguments.seed = Some(parse_seed(&seed)?),
+                None => return Err(String::from("Missing number after --seed")),
+            }
+        } else if let Some(seed) = arg.strip_prefix("--seed=") {
+            arguments.seed = Some(parse_seed(seed)?);
+        } else {
+            return Err(format!("Unknown argument '{}'", arg));
+        }
+    }
+    Ok(arguments)
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Parses the given value of "--seed".
+fn parse_seed(seed: &str) -> Result<u64, String> {
+    seed.parse()
+        .map_err(|_| format!("Invalid seed '{}', expected a non-negative integer", seed))
+}
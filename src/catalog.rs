@@ -0,0 +1,270 @@
+//! This module reads the official question catalog ("Fragenkatalog")
+//! of the Bundesnetzagentur, i.e. the file
+//! "resources/fragenkatalog/fragenkatalog.json", directly into
+//! Funkfragenhelfer's Question representation.
+//!
+//! The catalog consists of nested "sections", each with a "title".
+//! A section either contains further sections or a list of questions.
+//! As the nesting depth differs between chapters (and may change with
+//! a new catalog edition), the sections are walked recursively so that
+//! no question is lost, regardless of how deep it is nested.
+
+// IMPORTS SECTION //
+use crate::error::{FfhError, Result};
+use crate::helper;
+use crate::question::{Category, Question};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// STRUCT SECTION //
+/// The catalog's metadata, describing the catalog edition.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct CatalogMetadata {
+    /// The catalog's edition, e.g. "3. Auflage, März 2024"
+    #[serde(default)]
+    pub edition: String,
+    /// The date at which the catalog was issued
+    #[serde(default)]
+    pub issued_on: String,
+    /// The date from which on the catalog is used in exams
+    #[serde(default)]
+    pub valid_from: String,
+    /// The catalog's license
+    #[serde(default)]
+    pub license: String,
+}
+
+/// A question as read from the catalog, together with the information
+/// that is not part of the Question struct itself.
+#[derive(Clone, Debug)]
+pub struct CatalogQuestion {
+    /// The converted question
+    pub question: Question,
+    /// The question's class as given in the catalog (1: N, 2: E, 3: A)
+    pub class: String,
+}
+
+/// The full content of the catalog.
+#[derive(Clone, Debug)]
+pub struct Catalog {
+    /// The catalog's metadata
+    pub metadata: CatalogMetadata,
+    /// All questions of the catalog in the catalog's order
+    pub questions: Vec<CatalogQuestion>,
+}
+
+impl Catalog {
+    /// Returns clones of all catalog questions as plain Question instances.
+    pub fn get_questions(&self) -> Vec<Question> {
+        self.questions
+            .iter()
+            .map(|catalog_question| catalog_question.question.clone())
+            .collect()
+    }
+}
+
+/// Raw representation of the catalog JSON's root.
+#[derive(Deserialize)]
+struct RawCatalog {
+    #[serde(default)]
+    metadata: CatalogMetadata,
+    #[serde(default)]
+    sections: Vec<RawSection>,
+}
+
+/// Raw representation of a catalog section, which either contains
+/// further sections or questions.
+#[derive(Deserialize)]
+struct RawSection {
+    title: String,
+    #[serde(default)]
+    sections: Vec<RawSection>,
+    #[serde(default)]
+    questions: Vec<RawQuestion>,
+}
+
+/// Raw representation of a catalog question. Missing answers and
+/// pictures are either not given or null in the catalog.
+#[derive(Deserialize)]
+struct RawQuestion {
+    number: String,
+    #[serde(default)]
+    class: Option<String>,
+    question: String,
+    answer_a: Option<String>,
+    answer_b: Option<String>,
+    answer_c: Option<String>,
+    answer_d: Option<String>,
+    #[serde(default)]
+    picture_question: Option<String>,
+    #[serde(default)]
+    picture_a: Option<String>,
+    #[serde(default)]
+    picture_b: Option<String>,
+    #[serde(default)]
+    picture_c: Option<String>,
+    #[serde(default)]
+    picture_d: Option<String>,
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Converts the given raw question into a CatalogQuestion.
+///
+/// The question's category is the first letter of its number. If this letter
+/// is no known category, an error message is returned, as this means that the
+/// catalog format changed in a way that Funkfragenhelfer does not know yet.
+fn convert_question(
+    raw: RawQuestion,
+    section_path: &[String],
+) -> std::result::Result<CatalogQuestion, String> {
+    let Some(category) = Category::from_identifier(&raw.number) else {
+        return Err(format!("Unbekannte Kategorie der Frage {}", raw.number));
+    };
+    Ok(CatalogQuestion {
+        question: Question {
+            category,
+            identifier: raw.number,
+            section_path: section_path.to_vec(),
+            question: raw.question,
+            answer_a: raw.answer_a.unwrap_or_default(),
+            answer_b: raw.answer_b.unwrap_or_default(),
+            answer_c: raw.answer_c.unwrap_or_default(),
+            answer_d: raw.answer_d.unwrap_or_default(),
+            picture_question: raw.picture_question.unwrap_or_default(),
+            picture_a: raw.picture_a.unwrap_or_default(),
+            picture_b: raw.picture_b.unwrap_or_default(),
+            picture_c: raw.picture_c.unwrap_or_default(),
+            picture_d: raw.picture_d.unwrap_or_default(),
+        },
+        class: raw.class.unwrap_or_default(),
+    })
+}
+
+/// Recursively collects the questions of the given section and all of
+/// its subsections (at any depth).
+///
+/// ### Arguments
+/// * section: The section whose questions are collected
+/// * section_path: The titles of all parent sections
+/// * questions: The list into which the found questions are pushed
+///
+/// ### Return value
+/// * An error message if a question could not be converted
+fn collect_questions(
+    section: RawSection,
+    section_path: &mut Vec<String>,
+    questions: &mut Vec<CatalogQuestion>,
+) -> std::result::Result<(), String> {
+    section_path.push(section.title);
+    for raw_question in section.questions {
+        questions.push(convert_question(raw_question, section_path)?);
+    }
+    for subsection in section.sections {
+        collect_questions(subsection, section_path, questions)?;
+    }
+    section_path.pop();
+    Ok(())
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Parses the given catalog JSON text into a Catalog.
+///
+/// ### Return value
+/// * The Catalog, or an error message if the text is no valid catalog
+pub fn parse_catalog(catalog_json_str: &str) -> std::result::Result<Catalog, String> {
+    let raw_catalog: RawCatalog =
+        serde_json::from_str(catalog_json_str).map_err(|e| e.to_string())?;
+    let mut questions = Vec::new();
+    let mut section_path = Vec::new();
+    for section in raw_catalog.sections {
+        collect_questions(section, &mut section_path, &mut questions)?;
+    }
+    Ok(Catalog {
+        metadata: raw_catalog.metadata,
+        questions,
+    })
+}
+
+/// Loads the catalog JSON at the given path (usually
+/// "./resources/fragenkatalog/fragenkatalog.json").
+///
+/// ### Return value
+/// * The Catalog, or an FfhError::MissingAsset if the file does not exist,
+///   or another FfhError if it cannot be read or parsed
+pub fn load_catalog(filepath: &str) -> Result<Catalog> {
+    if !Path::new(filepath).exists() {
+        return Err(FfhError::MissingAsset {
+            path: filepath.to_string(),
+        });
+    }
+    let catalog_json_str = helper::read_filetext(filepath)?;
+    parse_catalog(&catalog_json_str).map_err(|message| FfhError::parse(filepath, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a catalog JSON with a nested section which contains a single
+    /// question with the given number.
+    fn get_catalog_json(number: &str) -> String {
+        format!(
+            r#"{{
+                "metadata": {{"edition": "Test"}},
+                "sections": [{{
+                    "title": "Teil",
+                    "sections": [{{
+                        "title": "Kapitel",
+                        "questions": [{{
+                            "number": "{}",
+                            "class": "1",
+                            "question": "Frage",
+                            "answer_a": "A",
+                            "answer_b": "B",
+                            "answer_c": "C",
+                            "answer_d": null
+                        }}]
+                    }}]
+                }}]
+            }}"#,
+            number
+        )
+    }
+
+    #[test]
+    fn nested_questions_keep_their_section_path() {
+        let catalog = parse_catalog(&get_catalog_json("NA101")).unwrap();
+
+        assert_eq!(catalog.metadata.edition, "Test");
+        assert_eq!(catalog.questions.len(), 1);
+        let question = &catalog.questions[0].question;
+        assert_eq!(question.category, Category::N);
+        assert_eq!(question.section_path, ["Teil", "Kapitel"]);
+        assert_eq!(question.answer_d, "");
+        assert_eq!(catalog.questions[0].class, "1");
+    }
+
+    #[test]
+    fn unknown_category_is_a_parse_error() {
+        let message = parse_catalog(&get_catalog_json("XA101")).unwrap_err();
+        assert!(message.contains("XA101"));
+    }
+
+    #[test]
+    fn unknown_category_in_a_catalog_file_is_an_ffh_error() {
+        let dir = std::env::temp_dir().join(format!("ffh-catalog-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let filepath = dir.join("fragenkatalog.json");
+        std::fs::write(&filepath, get_catalog_json("XA101")).unwrap();
+
+        let result = load_catalog(&filepath.to_string_lossy());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(result, Err(FfhError::Parse { .. })));
+    }
+
+    #[test]
+    fn the_shipped_catalog_can_be_read() {
+        let catalog = load_catalog("./resources/fragenkatalog/fragenkatalog.json").unwrap();
+        assert!(!catalog.questions.is_empty());
+    }
+}
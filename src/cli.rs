@@ -0,0 +1,272 @@
+//! This module contains Funkfragenhelfer's command-line mode ("--cli"), which
+//! runs the same learning loop as the GUI's practice view over a text
+//! input and output (usually stdin and stdout).
+//!
+//! Thereby, one can practice without a graphical display (e.g. over SSH) and
+//! script the learning loop. As all front ends drive the same learning session
+//! (see the library's session module), the command-line mode uses the same files
+//! (i.e., "config.json", "learning.json" and the answer history) as the GUI.
+//! Formulas are shown as Unicode text and pictures as file paths.
+
+// IMPORTS SECTION //
+use funkfragenhelfer::{
+    error::{self, ErrorAction, FfhError},
+    learning::PrintQuestion,
+    math,
+    picture::PictureIndex,
+    session::Session,
+};
+use std::io::{self, BufRead, Write};
+
+// CONSTANTS SECTION //
+/// Names of the shown (i.e., shuffled) answers, indexed as in PrintQuestion
+const ANSWER_NAMES: [&str; 4] = ["A", "B", "C", "D"];
+/// The help text which lists all commands of the learning loop
+const HELP: &str = "Befehle: a-d = Antworten, s = Überspringen, m = Markieren/Entmarkieren, \
+                    n <Text> = Notiz setzen (n allein löscht sie), t = Statistik, q = Beenden";
+
+// PUBLIC FUNCTION SECTION //
+/// Runs the command-line learning loop until the user quits or the input ends.
+///
+/// ### Arguments
+/// * session: The learning session
+/// * picture_index: The index of all question and answer picture files
+/// * input: The input from which the user's commands are read
+/// * output: The output to which questions and results are written
+///
+/// ### Return value
+/// * Nothing, or an io::Error if the input or output failed
+pub fn run(
+    session: &mut Session,
+    picture_index: &PictureIndex,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(output, "{}", HELP)?;
+    write_message(output, session)?;
+    write_question(output, session.get_print_question(), picture_index)?;
+    loop {
+        write!(output, "> ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        let command = line.trim().to_lowercase();
+        let identifier = session.get_print_question().question.identifier.clone();
+
+        // The note command keeps the case of the note's text
+        if command == "n" || command.starts_with("n ") {
+            let note = line.trim()[1..].trim();
+            let result = session.set_note(note);
+            if note.is_empty() {
+                writeln!(output, "Notiz der Frage {} gelöscht.", identifier)?;
+            } else {
+                writeln!(output, "Notiz der Frage {} gespeichert.", identifier)?;
+            }
+            write_error(output, result)?;
+            continue;
+        }
+
+        match command.as_str() {
+            "a" | "b" | "c" | "d" => {
+                let given_answer = ANSWER_NAMES
+                    .iter()
+                    .position(|name| name.to_lowercase() == command)
+                    .unwrap();
+                let result = session.answer(given_answer);
+                if session.is_given_answer_correct() == Some(true) {
+                    writeln!(output, "Korrekt!")?;
+                } else {
+                    writeln!(
+                        output,
+                        "Falsch! Richtige Antwort ist {:?}",
+                        session.get_print_question().get_correct_answer()
+                    )?;
+                }
+                if !session.get_note().is_empty() {
+                    writeln!(output, "Notiz: {}", session.get_note())?;
+                }
+                write_error(output, result)?;
+                write_message(output, session)?;
+            }
+            "s" => {}
+            "m" => {
+                let is_marked = !session.is_marked();
+                let result = session.set_marked(is_marked);
+                if is_marked {
+                    writeln!(output, "Frage {} markiert.", identifier)?;
+                } else {
+                    writeln!(output, "Markierung der Frage {} entfernt.", identifier)?;
+                }
+                write_error(output, result)?;
+                continue;
+            }
+            "t" => {
+                write_statistics(output, session)?;
+                continue;
+            }
+            "q" => break,
+            _ => {
+                writeln!(output, "{}", HELP)?;
+                continue;
+            }
+        }
+
+        // The question was answered or skipped: Continue with the next one
+        session.next_question();
+        write_question(output, session.get_print_question(), picture_index)?;
+    }
+    write_statistics(output, session)
+}
+
+/// Asks the user on the command line what to do with a file that could not be
+/// loaded on startup. This is the command-line counterpart of the GUI's
+/// run_error_screen().
+///
+/// ### Arguments
+/// * error: The error which occurred
+/// * actions: The actions which can be applied to the file (Quit is always
+///   offered; without any other action, Quit is returned right away)
+///
+/// ### Return value
+/// * The chosen action, or ErrorAction::Quit if the input ended
+pub fn ask_error_action(error: &FfhError, actions: &[ErrorAction]) -> ErrorAction {
+    println!("Fehler beim Laden: {}", error);
+    if actions.is_empty() {
+        return ErrorAction::Quit;
+    }
+    let stdin = io::stdin();
+    loop {
+        for action in actions {
+            match action {
+                ErrorAction::Repair => {
+                    println!("r = Reparieren (lesbare Inhalte bleiben erhalten)")
+                }
+                ErrorAction::Reset => println!("z = Zurücksetzen (der Inhalt geht verloren)"),
+                ErrorAction::Rebuild => println!(
+                    "h = Aus der Antworthistorie wiederherstellen (Markierungen, Notizen und \
+                     Tags aus der neuesten Sicherung)"
+                ),
+                ErrorAction::Quit => {}
+            }
+        }
+        println!("q = Beenden");
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            return ErrorAction::Quit;
+        }
+        let chosen_action = match line.trim().to_lowercase().as_str() {
+            "r" => ErrorAction::Repair,
+            "z" => ErrorAction::Reset,
+            "h" => ErrorAction::Rebuild,
+            "q" => return ErrorAction::Quit,
+            _ => continue,
+        };
+        if actions.contains(&chosen_action) {
+            return chosen_action;
+        }
+    }
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Writes the given question with its shuffled answers. Pictures are written as
+/// file paths (or as a note if their file is missing).
+fn write_question(
+    output: &mut impl Write,
+    print_question: &PrintQuestion,
+    picture_index: &PictureIndex,
+) -> io::Result<()> {
+    let question = &print_question.question;
+    writeln!(output)?;
+    writeln!(output, "Frage {}", question.identifier)?;
+    writeln!(output, "{}", math::to_unicode(&question.question))?;
+    if !question.picture_question.is_empty() {
+        write_picture(output, picture_index, &question.picture_question)?;
+    }
+    for (index, answer_name) in ANSWER_NAMES.iter().enumerate() {
+        let answer_text = print_question.get_shuffled_answer(index);
+        let answer_picture = print_question.get_shuffled_picture(index);
+        writeln!(
+            output,
+            "{}: {}",
+            answer_name,
+            math::to_unicode(&answer_text)
+        )?;
+        if !answer_picture.is_empty() {
+            write!(output, "   ")?;
+            write_picture(output, picture_index, &answer_picture)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the file path of the given picture, or a note if its file is missing.
+fn write_picture(
+    output: &mut impl Write,
+    picture_index: &PictureIndex,
+    picture: &str,
+) -> io::Result<()> {
+    match picture_index.get_path(picture) {
+        Some(path) => writeln!(output, "Bild: {}", path),
+        None => writeln!(output, "Bild '{}' fehlt", picture),
+    }
+}
+
+/// Writes the statistics of the current session and of the whole learning progress.
+fn write_statistics(output: &mut impl Write, session: &Session) -> io::Result<()> {
+    let statistics = session.get_statistics();
+    writeln!(output)?;
+    writeln!(output, "Aktuelle Session:")?;
+    writeln!(
+        output,
+        "Korrekt beantwortete Fragen: {}, {} %",
+        session.get_correct_answers_since_start(),
+        (session.get_correct_answers_since_start() * 100)
+            .checked_div(session.get_answers_since_start())
+            .unwrap_or(0)
+    )?;
+    writeln!(
+        output,
+        "Insgesamt beantwortete Fragen: {}",
+        session.get_answers_since_start()
+    )?;
+    writeln!(output, "Lernfortschritt:")?;
+    writeln!(
+        output,
+        "Korrekt beantwortete Fragen: {}, {} %",
+        statistics.correct_answers,
+        (statistics.correct_answers * 100)
+            .checked_div(statistics.questions)
+            .unwrap_or(0)
+    )?;
+    writeln!(
+        output,
+        "Noch nicht korrekt beantwortete Fragen: {}, {} %",
+        statistics.no_correct_answers,
+        (statistics.no_correct_answers * 100)
+            .checked_div(statistics.questions)
+            .unwrap_or(0)
+    )?;
+    writeln!(output, "Fragen insgesamt: {}", statistics.questions)?;
+    writeln!(output, "Heute fällige Fragen: {}", statistics.due_today)
+}
+
+/// Writes the session's message (e.g. about a deactivated study deck), if there
+/// is one which was not shown yet.
+fn write_message(output: &mut impl Write, session: &mut Session) -> io::Result<()> {
+    match session.take_message() {
+        Some(message) => writeln!(output, "{}", message),
+        None => Ok(()),
+    }
+}
+
+/// Writes the error of the given result (if any). As in the GUI, errors while
+/// saving are not fatal, as the current state is kept in memory and saved again
+/// with the next change.
+fn write_error(output: &mut impl Write, result: error::Result<()>) -> io::Result<()> {
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => writeln!(output, "Fehler beim Speichern: {}", e),
+    }
+}
@@ -1,15 +1,114 @@
 //! Includes the struct and associated implementations
 //! for Funkragenhelfer's main configuration, including
-//! the selected question categories and question filters.
+//! the selected question categories, catalog sections, study decks, the question
+//! filter expression and keyboard shortcuts.
 
 // IMPORTS SECTION //
+use crate::deck::Deck;
+use crate::error::Result;
+use crate::filter::FilterExpression;
 use crate::helper;
+use crate::migration::{CONFIG_SCHEMA, CONFIG_VERSION};
+use crate::paths;
+use crate::persistence::{self, Loaded};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+// CONSTANTS SECTION //
+/// Default path of the question catalog's JSON
+pub const DEFAULT_QUESTIONS_FILEPATH: &str = "./resources/fragenkatalog/fragenkatalog.json";
+/// Name of the answer history file (see the history module)
+const HISTORY_FILENAME: &str = "history.jsonl";
+/// Name of the pictures directory next to the question catalog's JSON
+const PICTURES_DIRNAME: &str = "svgs";
+
+// ENUM SECTION //
+/// The algorithms for choosing the next question if no filter applies
+/// (see the learning module's get_next_print_question()).
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+pub enum SchedulerMode {
+    /// Time-based Leitner scheduler: Questions are due again after an
+    /// interval that grows with their bin
+    #[default]
+    Leitner,
+    /// The original algorithm, which chooses questions with a fixed
+    /// probability per bin, regardless of the time of the last answer
+    Legacy,
+    /// SuperMemo 2 algorithm with an ease factor and interval per question
+    Sm2,
+    /// Free Spaced Repetition Scheduler with a stability and difficulty
+    /// per question
+    Fsrs,
+}
+
+impl SchedulerMode {
+    /// All scheduler modes
+    pub const ALL: [SchedulerMode; 4] = [
+        SchedulerMode::Leitner,
+        SchedulerMode::Legacy,
+        SchedulerMode::Sm2,
+        SchedulerMode::Fsrs,
+    ];
+}
+
 // STRUCT SECTION //
-#[derive(Serialize, Deserialize, Debug)]
+/// The keyboard shortcuts of the practice loop. Each action can have several
+/// keys, which are given by their names (e.g. "A", "1", "Enter" or "Space").
+/// Unknown key names are ignored by the front ends.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct KeyBindings {
+    /// Keys which choose the shown answer A
+    pub answer_a: Vec<String>,
+    /// Keys which choose the shown answer B
+    pub answer_b: Vec<String>,
+    /// Keys which choose the shown answer C
+    pub answer_c: Vec<String>,
+    /// Keys which choose the shown answer D
+    pub answer_d: Vec<String>,
+    /// Keys which continue with the next question after an answer
+    pub next: Vec<String>,
+    /// Keys which skip the current question
+    pub skip: Vec<String>,
+    /// Keys which mark or unmark the current question
+    pub mark: Vec<String>,
+}
+
+impl KeyBindings {
+    /// Returns the keys which choose the shown answer with the given index
+    /// (0 is answer A, as in PrintQuestion).
+    pub fn get_answer_keys(&self, index: usize) -> &[String] {
+        match index {
+            0 => &self.answer_a,
+            1 => &self.answer_b,
+            2 => &self.answer_c,
+            _ => &self.answer_d,
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    /// Returns the default shortcuts, i.e. 1-4 or A-D to answer, Enter or Space
+    /// for the next question, S to skip and M to mark.
+    fn default() -> Self {
+        let keys = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        KeyBindings {
+            answer_a: keys(&["1", "A"]),
+            answer_b: keys(&["2", "B"]),
+            answer_c: keys(&["3", "C"]),
+            answer_d: keys(&["4", "D"]),
+            next: keys(&["Enter", "Space"]),
+            skip: keys(&["S"]),
+            mark: keys(&["M"]),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Config {
+    /// The schema version of the config.json (see the migration module)
+    #[serde(default)]
+    pub version: u64,
     /// Include question category V (Verordnungen)?
     pub include_v: bool,
     /// Include question category B (Betriebliches)?
@@ -20,71 +119,391 @@ pub struct Config {
     pub include_e: bool,
     /// Include question category A (Technik Klasse A)?
     pub include_a: bool,
-    /// Filepath to learning.json (currently fixed)
+    /// Catalog sections (given as section paths, see the question module)
+    /// whose questions are excluded, including all of their subsections
+    #[serde(default)]
+    pub excluded_sections: Vec<Vec<String>>,
+    /// The user's study decks (see the deck module)
+    #[serde(default)]
+    pub decks: Vec<Deck>,
+    /// The name of the deck whose questions are learned instead of the
+    /// selected categories and sections (None: no deck)
+    #[serde(default)]
+    pub active_deck: Option<String>,
+    /// Filepath to learning.json; Relative paths are relative to the data dir
+    /// (see the paths module). The answer history is stored next to it.
     pub learning_filepath: String,
     /// Maximal learning "bin" (see learning module for more)
     pub max_learn_bin: u64,
-    /// Filter for wrongly answered questions?
-    pub prefer_wrong: bool,
-    /// Filter for user-marked questions?
-    pub prefer_marked: bool,
-    /// Filter for questions that weren't asked yet?
-    pub prefer_new: bool,
-    /// Path to questions.json (currently fixed)
+    /// Filter expression of the questions which are preferred when the next
+    /// question is chosen (see the filter module; None: no filter)
+    #[serde(default)]
+    pub filter: Option<FilterExpression>,
+    /// Path to the question catalog's JSON; Relative paths are looked up as
+    /// shipped resource (see the paths module). The pictures are expected in
+    /// the "svgs" directory next to it.
     pub questions_filepath: String,
+    /// Algorithm for choosing the next question if no filter applies
+    #[serde(default)]
+    pub scheduler_mode: SchedulerMode,
+    /// Only ask already answered questions which are due today (as long as
+    /// such questions exist; not used by the legacy scheduler)
+    #[serde(default)]
+    pub only_due_today: bool,
+    /// Keyboard shortcuts of the practice loop
+    #[serde(default)]
+    pub key_bindings: KeyBindings,
+    /// The data dir in which the Config and the learning files are stored
+    /// (determined at startup, hence not saved)
+    #[serde(skip)]
+    pub data_dir: String,
 }
 
 impl Config {
     /// Return a Config with default values, as if someone
     /// would learn for class E without filters.
-    fn new() -> Config {
+    pub fn new() -> Config {
         Config {
+            version: CONFIG_VERSION,
             include_v: true,
             include_b: true,
             include_n: true,
             include_e: false,
             include_a: false,
+            excluded_sections: Vec::new(),
+            decks: Vec::new(),
+            active_deck: None,
             learning_filepath: String::from("./learning/learning.json"),
             max_learn_bin: 5,
-            prefer_wrong: false,
-            prefer_marked: false,
-            prefer_new: false,
-            questions_filepath: String::from("./questions/questions.json"),
+            filter: None,
+            questions_filepath: String::from(DEFAULT_QUESTIONS_FILEPATH),
+            scheduler_mode: SchedulerMode::Leitner,
+            only_due_today: false,
+            key_bindings: KeyBindings::default(),
+            data_dir: String::from("."),
         }
     }
 
     /// Save the config in the config.json (the directory's existence
     /// was ensured beforehand with this module's load_config()).
-    pub fn save(&self) {
-        let json_str = serde_json::to_string_pretty(&self).unwrap();
-        helper::overwrite_file_str("./config/config.json", &json_str);
+    pub fn save(&self) -> Result<()> {
+        persistence::save_json(&self.get_config_filepath(), self)
+    }
+
+    /// Returns the path of the config.json in the data dir.
+    pub fn get_config_filepath(&self) -> String {
+        paths::resolve_data_path(&self.data_dir, paths::CONFIG_FILEPATH)
+    }
+
+    /// Returns the resolved path of the learning.json.
+    pub fn get_learning_filepath(&self) -> String {
+        paths::resolve_data_path(&self.data_dir, &self.learning_filepath)
+    }
+
+    /// Returns the path of the answer history (see the history module), which
+    /// is stored next to the learning.json.
+    pub fn get_history_filepath(&self) -> String {
+        let learning_filepath = self.get_learning_filepath();
+        let learning_dir = Path::new(&learning_filepath)
+            .parent()
+            .unwrap_or(Path::new("."));
+        learning_dir
+            .join(HISTORY_FILENAME)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Returns the resolved path of the question catalog's JSON.
+    pub fn get_questions_filepath(&self) -> String {
+        paths::resolve_resource_path(&self.questions_filepath)
+    }
+
+    /// Returns the directory of the question and answer pictures, i.e. the
+    /// "svgs" directory next to the question catalog's JSON.
+    pub fn get_pictures_dir(&self) -> String {
+        let questions_filepath = self.get_questions_filepath();
+        let catalog_dir = Path::new(&questions_filepath)
+            .parent()
+            .unwrap_or(Path::new("."));
+        catalog_dir
+            .join(PICTURES_DIRNAME)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Returns the active study deck, or None if no deck is active (or the
+    /// active deck does not exist anymore).
+    pub fn get_active_deck(&self) -> Option<&Deck> {
+        let active_deck = self.active_deck.as_ref()?;
+        self.decks.iter().find(|deck| &deck.name == active_deck)
     }
 
     /// Returns whether or not all categories were set off (used by the GUI to prevent that
     /// a user shuts off all questions).
     pub fn all_includes_false(&self) -> bool {
-        !self.include_a
-            && !self.include_b
-            && !self.include_e
-            && !self.include_n
-            && !self.include_v
+        !self.include_a && !self.include_b && !self.include_e && !self.include_n && !self.include_v
+    }
+
+    /// Returns whether or not the section with the given section path is included,
+    /// i.e. whether neither the section itself nor one of its parent sections
+    /// is excluded.
+    pub fn is_section_included(&self, section_path: &[String]) -> bool {
+        !self
+            .excluded_sections
+            .iter()
+            .any(|excluded_section| section_path.starts_with(excluded_section))
+    }
+
+    /// Includes or excludes the section with the given section path.
+    ///
+    /// If an excluded parent section prevents the inclusion, the parent section
+    /// is included again while all of its other subsections stay excluded.
+    ///
+    /// ### Arguments
+    /// * section_path: The path of the section that shall be (de)selected
+    /// * include: Whether the section shall be included or excluded
+    /// * all_section_paths: All section paths of the catalog (see the question
+    ///   module's get_section_paths())
+    pub fn set_section_included(
+        &mut self,
+        section_path: &[String],
+        include: bool,
+        all_section_paths: &[Vec<String>],
+    ) {
+        if include {
+            // Get the outermost excluded parent section (or the section itself)
+            let min_excluded_depth = self
+                .excluded_sections
+                .iter()
+                .filter(|excluded_section| section_path.starts_with(excluded_section))
+                .map(|excluded_section| excluded_section.len())
+                .min();
+            if let Some(min_excluded_depth) = min_excluded_depth {
+                self.excluded_sections
+                    .retain(|excluded_section| !section_path.starts_with(excluded_section));
+                // Below the formerly excluded parent section, all sections that are
+                // not on the way to the included section stay excluded
+                self.exclude_siblings(section_path, min_excluded_depth, all_section_paths);
+            }
+        } else {
+            self.excluded_sections
+                .retain(|excluded_section| !excluded_section.starts_with(section_path));
+            self.excluded_sections.push(section_path.to_vec());
+        }
+    }
+
+    /// Excludes all sections except for the given one (and its parent sections,
+    /// which are still included as far as they contain the given section).
+    pub fn select_only_section(
+        &mut self,
+        section_path: &[String],
+        all_section_paths: &[Vec<String>],
+    ) {
+        self.excluded_sections.clear();
+        self.exclude_siblings(section_path, 0, all_section_paths);
+    }
+
+    /// Returns the outermost sections which are included with all of their
+    /// subsections, e.g. to define a study deck by the current section selection
+    /// (see the deck module). If no section is excluded, no section is returned,
+    /// as then the whole catalog is included.
+    ///
+    /// ### Arguments
+    /// * all_section_paths: All section paths of the catalog (see the question
+    ///   module's get_section_paths())
+    pub fn get_fully_included_sections(
+        &self,
+        all_section_paths: &[Vec<String>],
+    ) -> Vec<Vec<String>> {
+        if self.excluded_sections.is_empty() {
+            return Vec::new();
+        }
+        let is_fully_included = |section_path: &[String]| {
+            self.is_section_included(section_path)
+                && !self
+                    .excluded_sections
+                    .iter()
+                    .any(|excluded_section| excluded_section.starts_with(section_path))
+        };
+        all_section_paths
+            .iter()
+            .filter(|section_path| {
+                is_fully_included(section_path)
+                    && (section_path.len() == 1
+                        || !is_fully_included(&section_path[..section_path.len() - 1]))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Excludes all sibling sections of the given section and of its parent sections,
+    /// starting with the parent section at the given depth.
+    fn exclude_siblings(
+        &mut self,
+        section_path: &[String],
+        start_depth: usize,
+        all_section_paths: &[Vec<String>],
+    ) {
+        for depth in start_depth..section_path.len() {
+            for other_section_path in all_section_paths {
+                let is_sibling = other_section_path.len() == depth + 1
+                    && other_section_path.starts_with(&section_path[..depth])
+                    && other_section_path[depth] != section_path[depth];
+                if is_sibling {
+                    self.excluded_sections.push(other_section_path.clone());
+                }
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    /// Returns the same Config as Config::new().
+    fn default() -> Self {
+        Config::new()
     }
 }
 
 // PUBLIC FUNCTION SECTION //
-/// Loads the config.json Config, or, if it doesn't exist, creates
-/// a new such JSON with default values.
-pub fn load_config() -> Config {
-    let config_dir_path = Path::new("./config");
-    if !config_dir_path.exists() {
-        helper::ensure_dir_existence("./config");
-    }
-    let config_file_path = Path::new("./config/config.json");
-    if !config_file_path.exists() {
-        let default_config = Config::new();
-        let config_json = serde_json::to_string_pretty(&default_config).unwrap();
-        helper::overwrite_file_str("./config/config.json", &config_json);
-    }
-    let config_text = helper::read_filetext("./config/config.json");
-    serde_json::from_str(&config_text).unwrap()
+/// Loads the config.json Config of the given data dir (see the paths module), or,
+/// if it doesn't exist, creates a new such JSON with default values.
+///
+/// An older config.json is upgraded to the current schema version (see the
+/// migration module). If the file is corrupted, it is restored from its newest
+/// readable backup (see the persistence module), and a warning for the user is
+/// returned too. If it cannot be restored, an FfhError is returned (see repair_config() and
+/// reset_config()).
+pub fn load_config(data_dir: &str) -> Result<Loaded<Config>> {
+    let config_filepath = ensure_config_dir(data_dir);
+    let mut loaded = persistence::load_json(&config_filepath, &CONFIG_SCHEMA, Config::new)?;
+    loaded.value.data_dir = data_dir.to_string();
+    Ok(loaded)
+}
+
+/// Repairs the config.json of the given data dir by setting all missing or
+/// unreadable settings to their default values (see the persistence module).
+pub fn repair_config(data_dir: &str) -> Result<()> {
+    let config_filepath = ensure_config_dir(data_dir);
+    persistence::repair_json(&config_filepath, &CONFIG_SCHEMA, |value| {
+        persistence::repair_struct(&Config::new(), value)
+    })
+    .map(|_| ())
+}
+
+/// Resets the config.json of the given data dir to the default values.
+pub fn reset_config(data_dir: &str) -> Result<()> {
+    let config_filepath = ensure_config_dir(data_dir);
+    persistence::reset_json(&config_filepath, Config::new).map(|_| ())
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Ensures that the config directory of the given data dir exists
+/// and returns the path of its config.json.
+fn ensure_config_dir(data_dir: &str) -> String {
+    let config_filepath = paths::resolve_data_path(data_dir, paths::CONFIG_FILEPATH);
+    if let Some(config_dir) = Path::new(&config_filepath).parent() {
+        helper::ensure_dir_existence(&config_dir.to_string_lossy());
+    }
+    config_filepath
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the section path with the given section names.
+    fn get_path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    /// Returns the section paths of a catalog with two top-level sections, of which
+    /// the first one has a subsection with subsections itself.
+    fn get_all_section_paths() -> Vec<Vec<String>> {
+        vec![
+            get_path(&["A"]),
+            get_path(&["A", "1"]),
+            get_path(&["A", "2"]),
+            get_path(&["A", "2", "a"]),
+            get_path(&["A", "2", "b"]),
+            get_path(&["B"]),
+            get_path(&["B", "1"]),
+        ]
+    }
+
+    #[test]
+    fn excluded_parent_excludes_its_subsections_until_it_is_included_again() {
+        let all_section_paths = get_all_section_paths();
+        let mut config = Config::new();
+
+        config.set_section_included(&get_path(&["A", "2"]), false, &all_section_paths);
+        config.set_section_included(&get_path(&["A"]), false, &all_section_paths);
+        assert_eq!(config.excluded_sections, [get_path(&["A"])]);
+        assert!(!config.is_section_included(&get_path(&["A", "2", "a"])));
+        assert!(config.is_section_included(&get_path(&["B", "1"])));
+
+        config.set_section_included(&get_path(&["A"]), true, &all_section_paths);
+        assert!(config.excluded_sections.is_empty());
+        assert!(config.is_section_included(&get_path(&["A", "2", "a"])));
+    }
+
+    #[test]
+    fn subsection_of_excluded_parent_is_included_by_excluding_its_siblings() {
+        let all_section_paths = get_all_section_paths();
+        let mut config = Config::new();
+
+        config.set_section_included(&get_path(&["A"]), false, &all_section_paths);
+        config.set_section_included(&get_path(&["A", "2", "a"]), true, &all_section_paths);
+        assert_eq!(
+            config.excluded_sections,
+            [get_path(&["A", "1"]), get_path(&["A", "2", "b"])]
+        );
+        assert!(config.is_section_included(&get_path(&["A"])));
+        assert!(config.is_section_included(&get_path(&["A", "2"])));
+        assert!(config.is_section_included(&get_path(&["A", "2", "a"])));
+        assert!(!config.is_section_included(&get_path(&["A", "1"])));
+        assert!(!config.is_section_included(&get_path(&["A", "2", "b"])));
+        assert!(config.is_section_included(&get_path(&["B"])));
+    }
+
+    #[test]
+    fn selecting_only_a_section_excludes_all_others() {
+        let all_section_paths = get_all_section_paths();
+        let mut config = Config::new();
+        config.set_section_included(&get_path(&["B", "1"]), false, &all_section_paths);
+
+        config.select_only_section(&get_path(&["A", "2"]), &all_section_paths);
+        assert_eq!(
+            config.excluded_sections,
+            [get_path(&["B"]), get_path(&["A", "1"])]
+        );
+        assert!(config.is_section_included(&get_path(&["A", "2", "a"])));
+        assert!(config.is_section_included(&get_path(&["A", "2", "b"])));
+        assert!(!config.is_section_included(&get_path(&["A", "1"])));
+        assert!(!config.is_section_included(&get_path(&["B", "1"])));
+    }
+
+    #[test]
+    fn fully_included_sections_are_the_outermost_ones_without_exclusions() {
+        let all_section_paths = get_all_section_paths();
+        let mut config = Config::new();
+        assert!(config
+            .get_fully_included_sections(&all_section_paths)
+            .is_empty());
+
+        config.set_section_included(&get_path(&["A", "2", "b"]), false, &all_section_paths);
+        assert_eq!(
+            config.get_fully_included_sections(&all_section_paths),
+            [
+                get_path(&["A", "1"]),
+                get_path(&["A", "2", "a"]),
+                get_path(&["B"])
+            ]
+        );
+
+        config.select_only_section(&get_path(&["A", "2"]), &all_section_paths);
+        assert_eq!(
+            config.get_fully_included_sections(&all_section_paths),
+            [get_path(&["A", "2"])]
+        );
+    }
 }
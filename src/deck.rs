@@ -0,0 +1,217 @@
+//! This module contains the user-defined study decks ("Lernstapel"), which
+//! select the questions to learn instead of the Config's question categories
+//! and sections.
+//!
+//! A deck is saved in the Config under its name and defined by any combination
+//! of tags (see the learning module's LearnState), question categories, catalog
+//! sections, a range of learning bins and a list of question identifiers. A
+//! question belongs to a deck if it fulfills all of the deck's criteria, whereby
+//! an empty criterion is fulfilled by every question. As tags and bins change
+//! while learning, a deck's questions are determined anew with each change (see
+//! the session module).
+
+// IMPORTS SECTION //
+use crate::learning::LearnStates;
+use crate::question::{Category, Question};
+use serde::{Deserialize, Serialize};
+
+// STRUCT SECTION //
+/// A user-defined study deck.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct Deck {
+    /// The deck's unique name
+    pub name: String,
+    /// Tags of which a question must have at least one (empty: any question)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Categories of which a question must be part (empty: any category)
+    #[serde(default)]
+    pub categories: Vec<Category>,
+    /// Catalog sections (given as section paths, see the question module) of which
+    /// a question must be part, including their subsections (empty: any section)
+    #[serde(default)]
+    pub sections: Vec<Vec<String>>,
+    /// Minimal learning bin of a question (None: no minimum); Questions which
+    /// were never answered count as bin 0
+    #[serde(default)]
+    pub min_bin: Option<u64>,
+    /// Maximal learning bin of a question (None: no maximum)
+    #[serde(default)]
+    pub max_bin: Option<u64>,
+    /// Identifiers of which a question must have one (empty: any question)
+    #[serde(default)]
+    pub identifiers: Vec<String>,
+}
+
+impl Deck {
+    /// Creates a new deck with the given name which contains all questions.
+    pub fn new(name: &str) -> Deck {
+        Deck {
+            name: name.to_string(),
+            tags: Vec::new(),
+            categories: Vec::new(),
+            sections: Vec::new(),
+            min_bin: None,
+            max_bin: None,
+            identifiers: Vec::new(),
+        }
+    }
+
+    /// Returns whether or not the given question belongs to this deck.
+    ///
+    /// ### Arguments
+    /// * question: The question to check
+    /// * learn_states: The current LearnStates, which contain the questions'
+    ///   tags and bins
+    pub fn contains(&self, question: &Question, learn_states: &LearnStates) -> bool {
+        let learn_state = learn_states.get(&question.identifier);
+        if !self.tags.is_empty()
+            && !learn_state.is_some_and(|learn_state| {
+                learn_state.tags.iter().any(|tag| self.tags.contains(tag))
+            })
+        {
+            return false;
+        }
+        if !self.categories.is_empty() && !self.categories.contains(&question.category) {
+            return false;
+        }
+        if !self.sections.is_empty()
+            && !self
+                .sections
+                .iter()
+                .any(|section| question.section_path.starts_with(section))
+        {
+            return false;
+        }
+        let bin = learn_state.map_or(0, |learn_state| learn_state.current_bin);
+        if self.min_bin.is_some_and(|min_bin| bin < min_bin)
+            || self.max_bin.is_some_and(|max_bin| bin > max_bin)
+        {
+            return false;
+        }
+        self.identifiers.is_empty() || self.identifiers.contains(&question.identifier)
+    }
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Returns the questions out of the given ones which belong to the given deck.
+pub fn get_deck_questions(
+    questions: &[Question],
+    deck: &Deck,
+    learn_states: &LearnStates,
+) -> Vec<Question> {
+    questions
+        .iter()
+        .filter(|question| deck.contains(question, learn_states))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::LearnState;
+    use crate::question::test_question;
+
+    /// Returns the identifiers of the given deck's questions out of the
+    /// questions NA101, NB101, EA101 and AA101.
+    fn get_deck_identifiers(deck: &Deck, learn_states: &LearnStates) -> Vec<String> {
+        let questions: Vec<Question> = ["NA101", "NB101", "EA101", "AA101"]
+            .iter()
+            // Each question is in the section of its category and the first two
+            // letters (e.g. "N" > "NA")
+            .map(|identifier| test_question(identifier, &[&identifier[..1], &identifier[..2]]))
+            .collect();
+        get_deck_questions(&questions, deck, learn_states)
+            .into_iter()
+            .map(|question| question.identifier)
+            .collect()
+    }
+
+    #[test]
+    fn new_deck_contains_all_questions() {
+        let deck = Deck::new("Alle");
+        assert_eq!(get_deck_identifiers(&deck, &LearnStates::new()).len(), 4);
+    }
+
+    #[test]
+    fn deck_contains_the_questions_of_its_categories_and_sections() {
+        let mut deck = Deck::new("Technik");
+        deck.categories = vec![Category::N, Category::E];
+        assert_eq!(
+            get_deck_identifiers(&deck, &LearnStates::new()),
+            vec!["NA101", "NB101", "EA101"]
+        );
+
+        deck.sections = vec![vec![String::from("N"), String::from("NB")]];
+        assert_eq!(
+            get_deck_identifiers(&deck, &LearnStates::new()),
+            vec!["NB101"]
+        );
+    }
+
+    #[test]
+    fn deck_contains_the_questions_with_one_of_its_tags() {
+        let mut learn_states = LearnStates::new();
+        for (identifier, tag) in [
+            ("NA101", "Formel"),
+            ("EA101", "Recht"),
+            ("AA101", "Sonstiges"),
+        ] {
+            learn_states.insert(
+                identifier.to_string(),
+                LearnState {
+                    tags: vec![tag.to_string()],
+                    ..LearnState::new()
+                },
+            );
+        }
+        let mut deck = Deck::new("Lernen");
+        deck.tags = vec![String::from("Formel"), String::from("Recht")];
+
+        assert_eq!(
+            get_deck_identifiers(&deck, &learn_states),
+            vec!["NA101", "EA101"]
+        );
+    }
+
+    #[test]
+    fn deck_contains_the_questions_of_its_bin_range() {
+        let mut learn_states = LearnStates::new();
+        for (identifier, bin) in [("NA101", 1), ("NB101", 3), ("EA101", 5)] {
+            learn_states.insert(
+                identifier.to_string(),
+                LearnState {
+                    current_bin: bin,
+                    ..LearnState::new()
+                },
+            );
+        }
+        let mut deck = Deck::new("Schwach");
+        deck.max_bin = Some(3);
+        // AA101 was never answered, hence in bin 0
+        assert_eq!(
+            get_deck_identifiers(&deck, &learn_states),
+            vec!["NA101", "NB101", "AA101"]
+        );
+
+        deck.min_bin = Some(1);
+        assert_eq!(
+            get_deck_identifiers(&deck, &learn_states),
+            vec!["NA101", "NB101"]
+        );
+    }
+
+    #[test]
+    fn deck_criteria_are_combined() {
+        let mut deck = Deck::new("Auswahl");
+        deck.identifiers = vec![String::from("NA101"), String::from("EA101")];
+        assert_eq!(
+            get_deck_identifiers(&deck, &LearnStates::new()),
+            vec!["NA101", "EA101"]
+        );
+
+        deck.categories = vec![Category::A];
+        assert!(get_deck_identifiers(&deck, &LearnStates::new()).is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+//! This module contains Funkfragenhelfer's error type, which is returned
+//! by all functions that load or save files.
+//!
+//! The error messages are written for the user (in German), as they are
+//! shown in the GUI's startup error screen.
+
+// IMPORTS SECTION //
+use std::fmt;
+use std::io;
+
+// ENUM SECTION //
+/// All errors which can occur while loading or saving Funkfragenhelfer's files.
+#[derive(Debug)]
+pub enum FfhError {
+    /// A file could not be read or written
+    Io { path: String, source: io::Error },
+    /// A file's content could not be parsed, e.g. because a field is missing
+    Parse { path: String, message: String },
+    /// A file was written with a schema version which this version of
+    /// Funkfragenhelfer does not support
+    SchemaVersion {
+        path: String,
+        found: u64,
+        supported: u64,
+    },
+    /// A resource which is shipped with Funkfragenhelfer (e.g. the question
+    /// catalog) does not exist
+    MissingAsset { path: String },
+    /// An answer was given which the question does not have (the index of the
+    /// answers A to D is 0 to 3)
+    InvalidAnswer { index: usize },
+}
+
+/// The actions which the user can choose if a file could not be loaded on
+/// startup (see the GUI's error screen and the command-line mode).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ErrorAction {
+    /// Repair the affected file, keeping all readable content
+    Repair,
+    /// Reset the affected file to its default content
+    Reset,
+    /// Rebuild the learning progress from the answer history (see the history
+    /// module's rebuild_learning()); Only offered for the learning.json
+    Rebuild,
+    /// Quit Funkfragenhelfer
+    Quit,
+}
+
+/// Funkfragenhelfer's result type.
+pub type Result<T> = std::result::Result<T, FfhError>;
+
+impl FfhError {
+    /// Creates an FfhError::Io for the given path.
+    pub fn io(path: &str, source: io::Error) -> FfhError {
+        FfhError::Io {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    /// Creates an FfhError::Parse for the given path.
+    pub fn parse(path: &str, message: impl ToString) -> FfhError {
+        FfhError::Parse {
+            path: path.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns the path of the file the error refers to, or None if the error
+    /// does not refer to a file.
+    pub fn get_path(&self) -> Option<&str> {
+        match self {
+            FfhError::Io { path, .. }
+            | FfhError::Parse { path, .. }
+            | FfhError::SchemaVersion { path, .. }
+            | FfhError::MissingAsset { path } => Some(path),
+            FfhError::InvalidAnswer { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for FfhError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FfhError::Io { path, source } => {
+                write!(
+                    f,
+                    "Die Datei '{}' konnte nicht gelesen oder geschrieben werden: {}",
+                    path, source
+                )
+            }
+            FfhError::Parse { path, message } => {
+                write!(f, "Die Datei '{}' ist fehlerhaft: {}", path, message)
+            }
+            FfhError::SchemaVersion {
+                path,
+                found,
+                supported,
+            } => write!(
+                f,
+                "Die Datei '{}' hat die Formatversion {}, unterstützt wird höchstens Version {}. \
+                 Sie wurde vermutlich mit einer neueren Version des Funkfragenhelfers erstellt.",
+                path, found, supported
+            ),
+            FfhError::MissingAsset { path } => {
+                write!(
+                    f,
+                    "Die Datei '{}' fehlt. Bitte installiere den Funkfragenhelfer neu.",
+                    path
+                )
+            }
+            FfhError::InvalidAnswer { index } => {
+                write!(
+                    f,
+                    "Die Antwort Nummer {} gibt es nicht, es gibt nur die Antworten A bis D.",
+                    index + 1
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FfhError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FfhError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
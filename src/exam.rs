@@ -0,0 +1,407 @@
+//! This module contains the exam simulation of Funkfragenhelfer.
+//!
+//! In contrast to the endless practice of the learning module, an exam
+//! consists of a fixed number of randomly drawn questions of one exam part
+//! (e.g. "Technische Kenntnisse Klasse N"), which have to be answered within
+//! a time limit. Whether or not an answer is correct is only revealed after
+//! the exam was submitted. The result is graded against the official pass
+//! mark. An exam does not change the LearnStates unless the result is
+//! explicitly applied to them.
+//!
+//! The question counts, pass marks and time limits follow the exam rules of
+//! the Bundesnetzagentur for the catalog's 3rd edition: The technical parts of
+//! classes E and A are larger than the other parts, but all parts require
+//! about 73 % correct answers.
+
+// IMPORTS SECTION //
+use crate::config::Config;
+use crate::error::Result;
+use crate::helper;
+use crate::history::{self, AnswerEvent, AnswerMode};
+use crate::learning::{self, Answer, LearnStates, PrintQuestion};
+use crate::question::{Category, Question};
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+// ENUM SECTION //
+/// The parts of the amateur radio exam, each of which is
+/// passed separately.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ExamPart {
+    /// Technische Kenntnisse Klasse N
+    TechnikN,
+    /// Technische Kenntnisse Klasse E
+    TechnikE,
+    /// Technische Kenntnisse Klasse A
+    TechnikA,
+    /// Betriebliche Kenntnisse
+    Betriebliches,
+    /// Kenntnisse von Vorschriften
+    Vorschriften,
+}
+
+impl ExamPart {
+    /// All exam parts in their usual order
+    pub const ALL: [ExamPart; 5] = [
+        ExamPart::TechnikN,
+        ExamPart::TechnikE,
+        ExamPart::TechnikA,
+        ExamPart::Betriebliches,
+        ExamPart::Vorschriften,
+    ];
+
+    /// Returns the exam part's name.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            ExamPart::TechnikN => "Technische Kenntnisse Klasse N",
+            ExamPart::TechnikE => "Technische Kenntnisse Klasse E",
+            ExamPart::TechnikA => "Technische Kenntnisse Klasse A",
+            ExamPart::Betriebliches => "Betriebliche Kenntnisse",
+            ExamPart::Vorschriften => "Kenntnisse von Vorschriften",
+        }
+    }
+
+    /// Returns the question category from which the exam part's questions are drawn.
+    pub fn get_category(&self) -> Category {
+        match self {
+            ExamPart::TechnikN => Category::N,
+            ExamPart::TechnikE => Category::E,
+            ExamPart::TechnikA => Category::A,
+            ExamPart::Betriebliches => Category::B,
+            ExamPart::Vorschriften => Category::V,
+        }
+    }
+
+    /// Returns the number of questions of the exam part.
+    pub fn get_question_count(&self) -> usize {
+        match self {
+            ExamPart::TechnikN => 25,
+            ExamPart::TechnikE => 34,
+            ExamPart::TechnikA => 51,
+            ExamPart::Betriebliches => 25,
+            ExamPart::Vorschriften => 25,
+        }
+    }
+
+    /// Returns the number of correct answers needed to pass the exam part.
+    pub fn get_pass_count(&self) -> usize {
+        match self {
+            ExamPart::TechnikN => 19,
+            ExamPart::TechnikE => 25,
+            ExamPart::TechnikA => 38,
+            ExamPart::Betriebliches => 19,
+            ExamPart::Vorschriften => 19,
+        }
+    }
+
+    /// Returns the time limit of the exam part in seconds.
+    pub fn get_duration_in_sec(&self) -> u64 {
+        let minutes = match self {
+            ExamPart::TechnikN => 45,
+            ExamPart::TechnikE => 60,
+            ExamPart::TechnikA => 90,
+            ExamPart::Betriebliches => 45,
+            ExamPart::Vorschriften => 45,
+        };
+        minutes * 60
+    }
+}
+
+// STRUCT SECTION //
+/// A running exam of one exam part.
+pub struct Exam {
+    /// The exam part
+    pub part: ExamPart,
+    /// The drawn questions with their randomly shuffled answers
+    pub questions: Vec<PrintQuestion>,
+    /// For each question, the index of the given (shuffled) answer,
+    /// or None if the question was not answered yet
+    pub given_answers: Vec<Option<usize>>,
+    /// The UNIX time at which the exam was started
+    pub start_time: u64,
+}
+
+/// The result of a single exam question.
+pub struct ExamQuestionResult {
+    /// The question's identifier
+    pub identifier: String,
+    /// The given answer as it was shown in the exam (i.e., with shuffled
+    /// answers), or None if the question was not answered
+    pub shuffled_answer: Option<Answer>,
+    /// The given answer as in the original catalog (where A is always
+    /// correct), or None if the question was not answered
+    pub given_answer: Option<Answer>,
+    /// Whether or not the given answer is correct
+    pub is_correct: bool,
+}
+
+/// The graded result of a submitted exam.
+pub struct ExamResult {
+    /// The exam part
+    pub part: ExamPart,
+    /// The results of all exam questions in the exam's order
+    pub question_results: Vec<ExamQuestionResult>,
+    /// Number of correctly answered questions
+    pub correct: usize,
+    /// Number of wrongly answered questions
+    pub wrong: usize,
+    /// Number of unanswered questions (which count as wrong)
+    pub unanswered: usize,
+    /// Whether or not the official pass mark was reached
+    pub passed: bool,
+    /// Time needed for the exam in seconds
+    pub duration_in_sec: u64,
+}
+
+impl Exam {
+    /// Starts a new exam of the given part by randomly drawing its questions out of
+    /// the given questions with the given random number generator (so that a seeded
+    /// generator yields the same exam each time). If the catalog contains fewer
+    /// questions of the part's category than the part's question count, all of
+    /// them are used.
+    pub fn new(part: ExamPart, questions: &[Question], rng: &mut dyn RngCore) -> Exam {
+        let category = part.get_category();
+        let part_questions: Vec<&Question> = questions
+            .iter()
+            .filter(|question| question.category == category)
+            .collect();
+        let chosen_questions: Vec<&Question> = part_questions
+            .choose_multiple(rng, part.get_question_count())
+            .copied()
+            .collect();
+        let exam_questions: Vec<PrintQuestion> = chosen_questions
+            .into_iter()
+            .map(|question| PrintQuestion::new(question.clone(), rng))
+            .collect();
+        let given_answers = vec![None; exam_questions.len()];
+        Exam {
+            part,
+            questions: exam_questions,
+            given_answers,
+            start_time: helper::get_current_unixtime_in_sec(),
+        }
+    }
+
+    /// Returns the number of seconds since the start of the exam.
+    pub fn get_elapsed_sec(&self) -> u64 {
+        helper::get_current_unixtime_in_sec().saturating_sub(self.start_time)
+    }
+
+    /// Returns the number of seconds until the time limit is reached.
+    pub fn get_remaining_sec(&self) -> u64 {
+        self.part
+            .get_duration_in_sec()
+            .saturating_sub(self.get_elapsed_sec())
+    }
+
+    /// Returns whether or not the time limit is reached.
+    pub fn is_time_over(&self) -> bool {
+        self.get_remaining_sec() == 0
+    }
+
+    /// Returns the number of questions which were answered up to now.
+    pub fn get_answered_count(&self) -> usize {
+        self.given_answers
+            .iter()
+            .filter(|given_answer| given_answer.is_some())
+            .count()
+    }
+
+    /// Submits the exam and grades it.
+    pub fn submit(&self) -> ExamResult {
+        let mut question_results = Vec::new();
+        let mut correct = 0;
+        let mut wrong = 0;
+        let mut unanswered = 0;
+        for (print_question, given_answer) in self.questions.iter().zip(&self.given_answers) {
+            let shuffled_answer = given_answer.map(Answer::from_index);
+            let given_answer = given_answer.map(|index| print_question.answer_shuffle[index]);
+            let is_correct = given_answer == Some(Answer::A);
+            match given_answer {
+                Some(_) if is_correct => correct += 1,
+                Some(_) => wrong += 1,
+                None => unanswered += 1,
+            }
+            question_results.push(ExamQuestionResult {
+                identifier: print_question.question.identifier.clone(),
+                shuffled_answer,
+                given_answer,
+                is_correct,
+            });
+        }
+        ExamResult {
+            part: self.part,
+            question_results,
+            correct,
+            wrong,
+            unanswered,
+            passed: correct >= self.part.get_pass_count(),
+            duration_in_sec: self.get_elapsed_sec().min(self.part.get_duration_in_sec()),
+        }
+    }
+}
+
+impl ExamResult {
+    /// Returns the share of correctly answered questions in percent.
+    pub fn get_correct_percentage(&self) -> usize {
+        (self.correct * 100)
+            .checked_div(self.question_results.len())
+            .unwrap_or(0)
+    }
+
+    /// Applies the exam result to the given LearnStates, as if each answered
+    /// question had been answered in the normal practice mode. Unanswered
+    /// questions are left unchanged. The answers are also recorded in the
+    /// answer history.
+    ///
+    /// ### Return value
+    /// * An FfhError if the answers could not be recorded in the answer history
+    ///   (the LearnStates are updated nonetheless)
+    pub fn apply_to_learning(&self, learning: &mut LearnStates, config: &Config) -> Result<()> {
+        let now = helper::get_current_unixtime_in_sec();
+        let mut events = Vec::new();
+        for question_result in &self.question_results {
+            let (Some(shuffled_answer), Some(original_answer)) = (
+                question_result.shuffled_answer,
+                question_result.given_answer,
+            ) else {
+                continue;
+            };
+            if question_result.is_correct {
+                learning::handle_correct_answer(learning, &question_result.identifier, config);
+            } else {
+                learning::handle_wrong_answer(
+                    learning,
+                    &question_result.identifier,
+                    original_answer,
+                );
+            }
+            events.push(AnswerEvent {
+                identifier: question_result.identifier.clone(),
+                shuffled_answer,
+                original_answer,
+                is_correct: question_result.is_correct,
+                unixtime: now,
+                response_time_in_ms: None,
+                mode: AnswerMode::Exam,
+            });
+        }
+        for event in &events {
+            history::append_event(event, config)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::question::test_question;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Returns the given number of questions of each category.
+    fn get_questions(count_per_category: usize) -> Vec<Question> {
+        let mut questions = Vec::new();
+        for category in Category::ALL {
+            for index in 0..count_per_category {
+                questions.push(test_question(&format!("{:?}A{:03}", category, index), &[]));
+            }
+        }
+        questions
+    }
+
+    /// Starts an exam of the given part and answers the given number of
+    /// questions correctly, the given number wrongly and leaves the rest
+    /// unanswered.
+    fn get_answered_exam(part: ExamPart, correct: usize, wrong: usize) -> Exam {
+        let mut exam = Exam::new(part, &get_questions(60), &mut StdRng::seed_from_u64(0));
+        for index in 0..correct + wrong {
+            let answer_shuffle = &exam.questions[index].answer_shuffle;
+            let correct_index = answer_shuffle
+                .iter()
+                .position(|answer| *answer == Answer::A)
+                .unwrap();
+            exam.given_answers[index] = Some(if index < correct {
+                correct_index
+            } else {
+                (correct_index + 1) % 4
+            });
+        }
+        exam
+    }
+
+    #[test]
+    fn exam_has_the_part_question_count_of_its_category() {
+        let questions = get_questions(60);
+        let mut rng = StdRng::seed_from_u64(0);
+        for part in ExamPart::ALL {
+            let exam = Exam::new(part, &questions, &mut rng);
+            assert_eq!(exam.questions.len(), part.get_question_count());
+            assert!(exam
+                .questions
+                .iter()
+                .all(|print_question| print_question.question.category == part.get_category()));
+        }
+    }
+
+    #[test]
+    fn exam_uses_all_questions_of_a_small_catalog() {
+        let exam = Exam::new(
+            ExamPart::TechnikA,
+            &get_questions(10),
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert_eq!(exam.questions.len(), 10);
+    }
+
+    #[test]
+    fn pass_marks_are_about_73_percent() {
+        for part in ExamPart::ALL {
+            let percentage = part.get_pass_count() * 100 / part.get_question_count();
+            assert!((73..=76).contains(&percentage), "{:?}", part);
+        }
+    }
+
+    #[test]
+    fn exam_is_passed_at_the_pass_count() {
+        for part in ExamPart::ALL {
+            let pass_count = part.get_pass_count();
+            let result = get_answered_exam(part, pass_count, 0).submit();
+            assert!(result.passed, "{:?}", part);
+            assert_eq!(result.correct, pass_count);
+            assert_eq!(result.unanswered, part.get_question_count() - pass_count);
+        }
+    }
+
+    #[test]
+    fn exam_is_failed_below_the_pass_count() {
+        for part in ExamPart::ALL {
+            let question_count = part.get_question_count();
+            let pass_count = part.get_pass_count();
+            let result =
+                get_answered_exam(part, pass_count - 1, question_count - pass_count + 1).submit();
+            assert!(!result.passed, "{:?}", part);
+            assert_eq!(result.correct, pass_count - 1);
+            assert_eq!(result.wrong, question_count - pass_count + 1);
+            assert_eq!(result.unanswered, 0);
+        }
+    }
+
+    #[test]
+    fn question_results_map_the_shuffled_answers_back() {
+        let result = get_answered_exam(ExamPart::Vorschriften, 1, 1).submit();
+
+        let correct_result = &result.question_results[0];
+        assert!(correct_result.is_correct);
+        assert_eq!(correct_result.given_answer, Some(Answer::A));
+        let wrong_result = &result.question_results[1];
+        assert!(!wrong_result.is_correct);
+        assert!(wrong_result.shuffled_answer.is_some());
+        assert_ne!(wrong_result.given_answer, Some(Answer::A));
+        let unanswered_result = &result.question_results[2];
+        assert!(!unanswered_result.is_correct);
+        assert_eq!(unanswered_result.given_answer, None);
+        assert_eq!(result.get_correct_percentage(), 4);
+    }
+}
@@ -0,0 +1,291 @@
+//! This module contains the filter expressions, which prefer questions with
+//! certain properties when the next question is chosen (see the learning
+//! module's get_next_print_question()).
+//!
+//! A filter expression is a tree: Its leaves are conditions on a question (its
+//! category or section) or on its LearnState (e.g. its bin, its number of wrong
+//! answers, its tags or the days since its last answer), and its inner nodes
+//! combine them with "and", "or" and "not". Hence, e.g. "marked and last answer
+//! wrong within the last week" or "bin at most 2 and category A" can be expressed.
+//! The Config stores the active filter expression, if any.
+
+// IMPORTS SECTION //
+use crate::learning::LearnState;
+use crate::question::{Category, Question};
+use crate::scheduler::SECONDS_PER_DAY;
+use serde::{Deserialize, Serialize};
+
+// ENUM SECTION //
+/// The comparisons of a number with a filter value.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub enum Comparison {
+    /// The number is at most the value
+    AtMost,
+    /// The number is at least the value
+    AtLeast,
+    /// The number equals the value
+    Equal,
+}
+
+impl Comparison {
+    /// All comparisons
+    pub const ALL: [Comparison; 3] = [Comparison::AtMost, Comparison::AtLeast, Comparison::Equal];
+
+    /// Returns whether or not the given number fulfills this comparison with
+    /// the given value.
+    pub fn compare(self, number: u64, value: u64) -> bool {
+        match self {
+            Comparison::AtMost => number <= value,
+            Comparison::AtLeast => number >= value,
+            Comparison::Equal => number == value,
+        }
+    }
+}
+
+/// A filter expression (see this module's comment). Questions which were never
+/// answered count as bin 0 with no correct and no wrong answers; Conditions on
+/// their last answer or their accuracy are not fulfilled.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum FilterExpression {
+    /// All subexpressions are fulfilled (true if there is none)
+    And(Vec<FilterExpression>),
+    /// At least one subexpression is fulfilled (false if there is none)
+    Or(Vec<FilterExpression>),
+    /// The subexpression is not fulfilled
+    Not(Box<FilterExpression>),
+    /// The question is marked
+    Marked,
+    /// The question was never answered
+    New,
+    /// The question's last answer was wrong
+    LastAnswerWrong,
+    /// The question has the given tag
+    Tag(String),
+    /// The question is part of the given category
+    Category(Category),
+    /// The question is part of the given section (given as section path, see
+    /// the question module) or one of its subsections
+    Section(Vec<String>),
+    /// The question's bin compares to the value
+    Bin { comparison: Comparison, value: u64 },
+    /// The question's number of correct answers compares to the value
+    CorrectCount { comparison: Comparison, value: u64 },
+    /// The question's number of wrong answers compares to the value
+    WrongCount { comparison: Comparison, value: u64 },
+    /// The number of full days since the question's last answer compares to the value
+    DaysSinceLastAnswer { comparison: Comparison, value: u64 },
+    /// The question's percentage of correct answers compares to the value
+    Accuracy { comparison: Comparison, value: u64 },
+}
+
+impl FilterExpression {
+    /// Returns whether or not the given question fulfills this filter expression.
+    ///
+    /// ### Arguments
+    /// * question: The question to check
+    /// * learn_state: The question's LearnState, or None if it has none yet
+    /// * current_unixtime: The current UNIX time in seconds
+    pub fn evaluate(
+        &self,
+        question: &Question,
+        learn_state: Option<&LearnState>,
+        current_unixtime: u64,
+    ) -> bool {
+        let answers = learn_state.map_or(0, |learn_state| learn_state.correct + learn_state.wrong);
+        match self {
+            FilterExpression::And(expressions) => expressions
+                .iter()
+                .all(|expression| expression.evaluate(question, learn_state, current_unixtime)),
+            FilterExpression::Or(expressions) => expressions
+                .iter()
+                .any(|expression| expression.evaluate(question, learn_state, current_unixtime)),
+            FilterExpression::Not(expression) => {
+                !expression.evaluate(question, learn_state, current_unixtime)
+            }
+            FilterExpression::Marked => learn_state.is_some_and(|learn_state| learn_state.marked),
+            FilterExpression::New => answers == 0,
+            FilterExpression::LastAnswerWrong => learn_state.is_some_and(|learn_state| {
+                learn_state.wrong > 0 && learn_state.rounds_since_wrong == 0
+            }),
+            FilterExpression::Tag(tag) => {
+                learn_state.is_some_and(|learn_state| learn_state.tags.contains(tag))
+            }
+            FilterExpression::Category(category) => question.category == *category,
+            FilterExpression::Section(section_path) => {
+                question.section_path.starts_with(section_path)
+            }
+            FilterExpression::Bin { comparison, value } => comparison.compare(
+                learn_state.map_or(0, |learn_state| learn_state.current_bin),
+                *value,
+            ),
+            FilterExpression::CorrectCount { comparison, value } => comparison.compare(
+                learn_state.map_or(0, |learn_state| learn_state.correct),
+                *value,
+            ),
+            FilterExpression::WrongCount { comparison, value } => comparison.compare(
+                learn_state.map_or(0, |learn_state| learn_state.wrong),
+                *value,
+            ),
+            FilterExpression::DaysSinceLastAnswer { comparison, value } => {
+                learn_state.is_some_and(|learn_state| {
+                    answers > 0
+                        && comparison.compare(
+                            current_unixtime.saturating_sub(learn_state.time_last_answer)
+                                / SECONDS_PER_DAY,
+                            *value,
+                        )
+                })
+            }
+            FilterExpression::Accuracy { comparison, value } => {
+                learn_state.is_some_and(|learn_state| {
+                    answers > 0 && comparison.compare(learn_state.correct * 100 / answers, *value)
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::question::test_question;
+
+    /// Returns a marked LearnState in bin 2 with 3 correct and 1 wrong answers,
+    /// whose last answer (at day 10) was wrong and which has the tag "Formel".
+    fn get_learn_state() -> LearnState {
+        LearnState {
+            current_bin: 2,
+            correct: 3,
+            wrong: 1,
+            marked: true,
+            rounds_since_wrong: 0,
+            time_last_answer: 10 * SECONDS_PER_DAY,
+            tags: vec![String::from("Formel")],
+            ..LearnState::new()
+        }
+    }
+
+    #[test]
+    fn comparisons_compare_the_number_with_the_value() {
+        assert!(Comparison::AtMost.compare(2, 2));
+        assert!(!Comparison::AtMost.compare(3, 2));
+        assert!(Comparison::AtLeast.compare(3, 2));
+        assert!(!Comparison::AtLeast.compare(1, 2));
+        assert!(Comparison::Equal.compare(2, 2));
+        assert!(!Comparison::Equal.compare(1, 2));
+    }
+
+    #[test]
+    fn conditions_check_the_learn_state() {
+        let question = test_question("NA101", &["N", "NA"]);
+        let learn_state = get_learn_state();
+        let now = 17 * SECONDS_PER_DAY + 100;
+        let is_fulfilled =
+            |expression: FilterExpression| expression.evaluate(&question, Some(&learn_state), now);
+
+        assert!(is_fulfilled(FilterExpression::Marked));
+        assert!(!is_fulfilled(FilterExpression::New));
+        assert!(is_fulfilled(FilterExpression::LastAnswerWrong));
+        assert!(is_fulfilled(FilterExpression::Tag(String::from("Formel"))));
+        assert!(!is_fulfilled(FilterExpression::Tag(String::from("Recht"))));
+        assert!(is_fulfilled(FilterExpression::Bin {
+            comparison: Comparison::Equal,
+            value: 2
+        }));
+        assert!(is_fulfilled(FilterExpression::CorrectCount {
+            comparison: Comparison::AtLeast,
+            value: 3
+        }));
+        assert!(!is_fulfilled(FilterExpression::WrongCount {
+            comparison: Comparison::AtLeast,
+            value: 2
+        }));
+        assert!(is_fulfilled(FilterExpression::DaysSinceLastAnswer {
+            comparison: Comparison::Equal,
+            value: 7
+        }));
+        assert!(is_fulfilled(FilterExpression::Accuracy {
+            comparison: Comparison::Equal,
+            value: 75
+        }));
+    }
+
+    #[test]
+    fn conditions_check_the_question() {
+        let question = test_question("NA101", &["N", "NA"]);
+        let is_fulfilled = |expression: FilterExpression| expression.evaluate(&question, None, 0);
+
+        assert!(is_fulfilled(FilterExpression::Category(Category::N)));
+        assert!(!is_fulfilled(FilterExpression::Category(Category::E)));
+        assert!(is_fulfilled(FilterExpression::Section(vec![String::from(
+            "N"
+        )])));
+        assert!(is_fulfilled(FilterExpression::Section(vec![
+            String::from("N"),
+            String::from("NA")
+        ])));
+        assert!(!is_fulfilled(FilterExpression::Section(vec![
+            String::from("N"),
+            String::from("NB")
+        ])));
+    }
+
+    #[test]
+    fn new_questions_count_as_bin_zero_without_answers() {
+        let question = test_question("NA101", &["N", "NA"]);
+        let is_fulfilled = |expression: FilterExpression| expression.evaluate(&question, None, 0);
+
+        assert!(is_fulfilled(FilterExpression::New));
+        assert!(!is_fulfilled(FilterExpression::Marked));
+        assert!(!is_fulfilled(FilterExpression::LastAnswerWrong));
+        assert!(is_fulfilled(FilterExpression::Bin {
+            comparison: Comparison::Equal,
+            value: 0
+        }));
+        assert!(is_fulfilled(FilterExpression::WrongCount {
+            comparison: Comparison::AtMost,
+            value: 0
+        }));
+        // Conditions on the last answer or the accuracy are not fulfilled
+        assert!(!is_fulfilled(FilterExpression::DaysSinceLastAnswer {
+            comparison: Comparison::AtLeast,
+            value: 0
+        }));
+        assert!(!is_fulfilled(FilterExpression::Accuracy {
+            comparison: Comparison::AtMost,
+            value: 100
+        }));
+        // The same holds for a LearnState without answers (e.g. only with a tag)
+        let learn_state = LearnState::new();
+        assert!(FilterExpression::New.evaluate(&question, Some(&learn_state), 0));
+        assert!(!FilterExpression::Accuracy {
+            comparison: Comparison::AtMost,
+            value: 100
+        }
+        .evaluate(&question, Some(&learn_state), 0));
+    }
+
+    #[test]
+    fn expressions_are_combined() {
+        let question = test_question("NA101", &["N", "NA"]);
+        let learn_state = get_learn_state();
+        let is_fulfilled =
+            |expression: FilterExpression| expression.evaluate(&question, Some(&learn_state), 0);
+        let marked_and_category_a = FilterExpression::And(vec![
+            FilterExpression::Marked,
+            FilterExpression::Category(Category::A),
+        ]);
+        let marked_or_category_a = FilterExpression::Or(vec![
+            FilterExpression::Marked,
+            FilterExpression::Category(Category::A),
+        ]);
+
+        assert!(!is_fulfilled(marked_and_category_a.clone()));
+        assert!(is_fulfilled(marked_or_category_a));
+        assert!(is_fulfilled(FilterExpression::Not(Box::new(
+            marked_and_category_a
+        ))));
+        assert!(is_fulfilled(FilterExpression::And(Vec::new())));
+        assert!(!is_fulfilled(FilterExpression::Or(Vec::new())));
+    }
+}
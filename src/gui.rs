@@ -8,264 +8,2140 @@
 //! have to edit this file as the other modules are GUI-framework-agnostic.
 
 // IMPORTS SECTION //
-use crate::{
-    config::Config,
-    learning::{self, save_learning, Answer, LearnStates, Statistics},
-    question,
-};
 use eframe::{
-    egui::{self, FontId, RichText, Vec2},
+    egui::{self, text::LayoutJob, Align, FontId, RichText, Sense, Stroke, TextFormat, Ui, Vec2},
     epaint::Color32,
 };
-use std::path::Path;
+use funkfragenhelfer::{
+    catalog::CatalogMetadata,
+    config::SchedulerMode,
+    deck::{self, Deck},
+    error::{self, ErrorAction, FfhError},
+    exam::{Exam, ExamPart, ExamResult},
+    filter::{Comparison, FilterExpression},
+    learning::{self, Answer, LearnState, LearnStates},
+    math::{self, MathNode, TextPart},
+    picture::PictureIndex,
+    profiles,
+    question::{self, Category, Question},
+    search::{SearchIndex, SearchResult},
+    session::Session,
+};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
 
 // CONSTANTS SECTION //
 /// Maximal image width for the GUI display
 const MAX_IMAGE_WIDTH: f32 = 250.0;
 /// Maximal image height for the GUI display
 const MAX_IMAGE_HEIGHT: f32 = 250.0;
+/// Font size of question and answer texts (including formulas)
+const TEXT_FONT_SIZE: f32 = 14.0;
+/// Font size factor for superscripts and subscripts in formulas
+const SCRIPT_FONT_FACTOR: f32 = 0.7;
+
+/// Names of the shown (i.e., shuffled) answers, indexed as in PrintQuestion
+const ANSWER_NAMES: [&str; 4] = ["A", "B", "C", "D"];
+/// Minimal number of times the same wrong answer must have been chosen
+/// so that a question is listed as confusion
+const CONFUSION_MIN_COUNT: u64 = 2;
+/// Maximal number of search results which are listed at once
+const MAX_SEARCH_RESULTS: usize = 100;
+/// Name of the "deck" which stands for the selected categories and sections
+const NO_DECK_NAME: &str = "Keiner (Fragekategorien und Kapitel)";
+
+// ENUM SECTION //
+/// The ways in which the main GUI can be left (see run()).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GuiExit {
+    /// The window was closed
+    Quit,
+    /// The user wants to switch to (or manage) another profile
+    SwitchProfile,
+}
+
+/// The views which can be selected as "Modus".
+#[derive(Clone, Copy, PartialEq)]
+enum View {
+    /// The normal practice
+    Practice,
+    /// The exam simulation
+    Exam,
+    /// The list of commonly confused answers
+    Confusions,
+    /// The free-text search across all questions
+    Search,
+    /// The catalog browser, in which the catalog can be read like a book
+    Catalog,
+}
+
+/// The filters of the catalog view by the questions' learning bin.
+#[derive(Clone, Copy, PartialEq)]
+enum BinFilter {
+    /// All questions
+    All,
+    /// The questions which were never answered
+    Unanswered,
+    /// The answered questions in the given bin
+    Bin(u64),
+}
+
+// STRUCTS SECTION //
+/// The state of the exam simulation view.
+struct ExamView {
+    /// The exam part of the next exam
+    part: ExamPart,
+    /// The running or last submitted exam
+    exam: Option<Exam>,
+    /// The result of the last submitted exam (None while the exam is running)
+    result: Option<ExamResult>,
+    /// Whether or not the result was already applied to the LearnStates
+    has_applied_result: bool,
+}
+
+/// The state of the search view.
+struct SearchView {
+    /// The index of all questions
+    index: SearchIndex,
+    /// The current search query
+    query: String,
+    /// The results of the current search query
+    results: Vec<SearchResult>,
+}
+
+/// The state of the catalog view.
+struct CatalogView {
+    /// The filter by the questions' learning bin
+    bin_filter: BinFilter,
+    /// Whether or not only the marked questions are shown
+    only_marked: bool,
+}
+
+impl CatalogView {
+    /// Returns whether or not a question with the given LearnState passes the
+    /// filters of the catalog view.
+    fn is_shown(&self, learn_state: Option<&LearnState>) -> bool {
+        if self.only_marked && !learn_state.is_some_and(|learn_state| learn_state.marked) {
+            return false;
+        }
+        let is_answered =
+            learn_state.is_some_and(|learn_state| learn_state.correct + learn_state.wrong > 0);
+        match self.bin_filter {
+            BinFilter::All => true,
+            BinFilter::Unanswered => !is_answered,
+            BinFilter::Bin(bin) => {
+                is_answered && learn_state.is_some_and(|learn_state| learn_state.current_bin == bin)
+            }
+        }
+    }
+}
+
+/// The state of the editor of study decks.
+struct DeckEditor {
+    /// The new or edited deck
+    deck: Deck,
+    /// The deck's tags as entered, separated by commas
+    tags: String,
+    /// The deck's question identifiers as entered, separated by commas or whitespace
+    identifiers: String,
+    /// A message about the last saved deck (e.g. that it was deactivated), if any
+    message: Option<String>,
+}
+
+impl DeckEditor {
+    /// Creates the editor of the given deck.
+    fn new(deck: Deck) -> DeckEditor {
+        DeckEditor {
+            tags: deck.tags.join(", "),
+            identifiers: deck.identifiers.join(", "),
+            deck,
+            message: None,
+        }
+    }
+}
+
+// PRIVATE FUNCTIONS SECTION //
+/// Returns the name of the given scheduler mode as shown in the GUI.
+fn get_scheduler_mode_name(scheduler_mode: SchedulerMode) -> &'static str {
+    match scheduler_mode {
+        SchedulerMode::Leitner => "Lernkartei",
+        SchedulerMode::Legacy => "Klassisch",
+        SchedulerMode::Sm2 => "SM-2",
+        SchedulerMode::Fsrs => "FSRS",
+    }
+}
+
+/// Stores the error of the given result (if any) as the last error, which is
+/// shown to the user. Errors while saving are not fatal, as the current state
+/// is kept in memory and saved again with the next change.
+fn record_error(result: error::Result<()>, last_error: &mut Option<String>) {
+    if let Err(e) = result {
+        *last_error = Some(e.to_string());
+    }
+}
+
+/// Returns whether or not one of the given keys (given by their names, see the
+/// config module's KeyBindings) was pressed in the current frame. While the user
+/// types into a text field (e.g. a note), no shortcut is pressed.
+fn is_shortcut_pressed(ui: &Ui, key_names: &[String]) -> bool {
+    if ui.ctx().wants_keyboard_input() {
+        return false;
+    }
+    key_names
+        .iter()
+        .filter_map(|key_name| egui::Key::from_name(key_name))
+        .any(|key| ui.input(|input| input.key_pressed(key)))
+}
+
+/// Returns the hint for the given keyboard shortcuts which is shown on buttons,
+/// e.g. " [1/A]", or an empty string if none of the keys is known.
+fn get_shortcut_hint(key_names: &[String]) -> String {
+    let names: Vec<&str> = key_names
+        .iter()
+        .filter_map(|key_name| egui::Key::from_name(key_name))
+        .map(|key| key.name())
+        .collect();
+    if names.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", names.join("/"))
+    }
+}
+
+/// Formats the given number of seconds as "mm:ss".
+fn format_duration(seconds: u64) -> String {
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Shows the commonly confused answers of the given questions, i.e. the questions
+/// for which the same wrong answer was chosen repeatedly, with the correct and the
+/// chosen wrong answer side by side.
+fn show_confusions_view(
+    ui: &mut Ui,
+    questions: &[Question],
+    picture_index: &PictureIndex,
+    learn_states: &LearnStates,
+) {
+    let confusions = learning::get_confusions(questions, learn_states, CONFUSION_MIN_COUNT);
+    if confusions.is_empty() {
+        ui.label(format!(
+            "Bisher wurde bei keiner Frage dieselbe falsche Antwort mindestens {}-mal gewählt.",
+            CONFUSION_MIN_COUNT
+        ));
+        return;
+    }
+    for confusion in &confusions {
+        ui.separator();
+        ui.label(
+            RichText::new(format!(
+                "Frage {} ({}-mal dieselbe falsche Antwort gewählt):",
+                confusion.question.identifier, confusion.count
+            ))
+            .strong(),
+        );
+        show_text_with_math(ui, &confusion.question.question);
+        if !confusion.question.picture_question.is_empty() {
+            show_picture(ui, picture_index, &confusion.question.picture_question);
+        }
+        ui.columns(2, |columns| {
+            let answers = [
+                ("Richtige Antwort:", Answer::A),
+                ("Häufig gewählte Antwort:", confusion.wrong_answer),
+            ];
+            for (column, (title, answer)) in columns.iter_mut().zip(answers) {
+                column.label(RichText::new(title).strong());
+                show_text_with_math(
+                    column,
+                    &learning::get_answer_text(&confusion.question, answer),
+                );
+                let picture = learning::get_answer_picture(&confusion.question, answer);
+                if !picture.is_empty() {
+                    show_picture(column, picture_index, &picture);
+                }
+            }
+        });
+    }
+}
+
+/// Shows and handles the selection of the active study deck. A change which
+/// would leave no question to ask is reverted.
+fn show_deck_selection(ui: &mut Ui, session: &mut Session, last_error: &mut Option<String>) {
+    ui.horizontal(|ui| {
+        ui.label("Lernstapel:");
+        let previous_active_deck = session.config.active_deck.clone();
+        let selected_text = session
+            .config
+            .get_active_deck()
+            .map_or(NO_DECK_NAME, |deck| deck.name.as_str())
+            .to_string();
+        let deck_names: Vec<String> = session
+            .config
+            .decks
+            .iter()
+            .map(|deck| deck.name.clone())
+            .collect();
+        let mut has_changed = false;
+        egui::ComboBox::from_id_source("active_deck")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                has_changed |= ui
+                    .selectable_value(&mut session.config.active_deck, None, NO_DECK_NAME)
+                    .clicked();
+                for deck_name in deck_names {
+                    has_changed |= ui
+                        .selectable_value(
+                            &mut session.config.active_deck,
+                            Some(deck_name.clone()),
+                            deck_name,
+                        )
+                        .clicked();
+                }
+            });
+        if has_changed {
+            if session.update_eligible_questions() {
+                record_error(session.config.save(), last_error);
+            } else {
+                session.config.active_deck = previous_active_deck;
+            }
+        }
+    });
+}
+
+/// Shows the management of the study decks, i.e. the list of all decks (each of
+/// which can be edited or deleted) and the editor of a new or an existing deck.
+fn show_deck_management(
+    ui: &mut Ui,
+    deck_editor: &mut DeckEditor,
+    session: &mut Session,
+    section_paths: &[Vec<String>],
+    last_error: &mut Option<String>,
+) {
+    // All decks with their current number of questions
+    let mut deleted_deck_name = None;
+    for deck in &session.config.decks {
+        ui.horizontal(|ui| {
+            let question_count =
+                deck::get_deck_questions(session.get_questions(), deck, &session.learn_states)
+                    .len();
+            ui.label(format!("{} ({} Fragen)", deck.name, question_count));
+            if ui.small_button("Bearbeiten").clicked() {
+                *deck_editor = DeckEditor::new(deck.clone());
+            }
+            if ui.small_button("Löschen").clicked() {
+                deleted_deck_name = Some(deck.name.clone());
+            }
+        });
+    }
+    if let Some(deleted_deck_name) = deleted_deck_name {
+        session
+            .config
+            .decks
+            .retain(|deck| deck.name != deleted_deck_name);
+        if session.config.active_deck.as_ref() == Some(&deleted_deck_name) {
+            session.config.active_deck = None;
+            session.update_eligible_questions();
+        }
+        record_error(session.config.save(), last_error);
+    }
+
+    // The editor of a new or an existing deck (a deck is replaced by a new
+    // deck with the same name)
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.label("Name:");
+        ui.text_edit_singleline(&mut deck_editor.deck.name);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Tags (durch Kommas getrennt, leer: alle Fragen):");
+        ui.text_edit_singleline(&mut deck_editor.tags);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Fragekategorien (keine: alle):");
+        for category in Category::ALL {
+            let mut is_selected = deck_editor.deck.categories.contains(&category);
+            if ui
+                .checkbox(&mut is_selected, format!("{:?}", category))
+                .changed()
+            {
+                if is_selected {
+                    deck_editor.deck.categories.push(category);
+                } else {
+                    deck_editor
+                        .deck
+                        .categories
+                        .retain(|other| *other != category);
+                }
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        if deck_editor.deck.sections.is_empty() {
+            ui.label("Kapitel: alle");
+        } else {
+            ui.label(format!(
+                "Kapitel: {} ausgewählt",
+                deck_editor.deck.sections.len()
+            ));
+        }
+        if ui
+            .small_button("Aktuelle Kapitelauswahl übernehmen")
+            .clicked()
+        {
+            deck_editor.deck.sections = session.config.get_fully_included_sections(section_paths);
+        }
+        if ui.small_button("Alle Kapitel").clicked() {
+            deck_editor.deck.sections.clear();
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Lerntopf von");
+        show_bin_limit(
+            ui,
+            &mut deck_editor.deck.min_bin,
+            session.config.max_learn_bin,
+        );
+        ui.label("bis");
+        show_bin_limit(
+            ui,
+            &mut deck_editor.deck.max_bin,
+            session.config.max_learn_bin,
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Fragenummern (leer: alle Fragen):");
+        ui.text_edit_singleline(&mut deck_editor.identifiers);
+    });
+
+    let name = deck_editor.deck.name.trim().to_string();
+    if ui
+        .add_enabled(!name.is_empty(), egui::Button::new("Lernstapel speichern"))
+        .clicked()
+    {
+        let mut deck = deck_editor.deck.clone();
+        deck.name = name;
+        deck.tags = deck_editor
+            .tags
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        deck.identifiers = deck_editor
+            .identifiers
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|identifier| !identifier.is_empty())
+            .map(|identifier| identifier.to_uppercase())
+            .collect();
+        match session
+            .config
+            .decks
+            .iter_mut()
+            .find(|other| other.name == deck.name)
+        {
+            Some(other) => *other = deck.clone(),
+            None => session.config.decks.push(deck.clone()),
+        }
+        // If the edited deck is active, but now matches no questions, it is
+        // deactivated, as there would be no question to ask otherwise
+        let mut message = None;
+        if session.config.active_deck.as_ref() == Some(&deck.name)
+            && !session.update_eligible_questions()
+        {
+            session.config.active_deck = None;
+            session.update_eligible_questions();
+            message = Some(format!(
+                "Der Lernstapel \"{}\" enthält keine Fragen mehr und wurde deaktiviert.",
+                deck.name
+            ));
+        }
+        record_error(session.config.save(), last_error);
+        *deck_editor = DeckEditor::new(Deck::new(""));
+        deck_editor.message = message;
+    }
+    if let Some(message) = &deck_editor.message {
+        ui.label(RichText::new(message).color(Color32::RED));
+    }
+}
+
+/// Shows and handles a limit of a study deck's bin range, which is either
+/// off (None) or a bin up to the given maximal bin.
+fn show_bin_limit(ui: &mut Ui, bin_limit: &mut Option<u64>, max_learn_bin: u64) {
+    let mut has_limit = bin_limit.is_some();
+    if ui.checkbox(&mut has_limit, "").changed() {
+        *bin_limit = has_limit.then_some(0);
+    }
+    match bin_limit {
+        Some(bin) => {
+            ui.add(egui::DragValue::new(bin).clamp_range(0..=max_learn_bin));
+        }
+        None => {
+            ui.label("beliebig");
+        }
+    }
+}
+
+/// Returns the name of the given comparison as shown in the filter builder.
+fn get_comparison_name(comparison: Comparison) -> &'static str {
+    match comparison {
+        Comparison::AtMost => "höchstens",
+        Comparison::AtLeast => "mindestens",
+        Comparison::Equal => "genau",
+    }
+}
+
+/// Returns the name of the given filter expression's kind as shown in the
+/// filter builder.
+fn get_filter_kind_name(expression: &FilterExpression) -> &'static str {
+    match expression {
+        FilterExpression::And(_) => "Alle Bedingungen (und)",
+        FilterExpression::Or(_) => "Eine der Bedingungen (oder)",
+        FilterExpression::Not(_) => "Nicht",
+        FilterExpression::Marked => "Markiert",
+        FilterExpression::New => "Noch nicht beantwortet",
+        FilterExpression::LastAnswerWrong => "Letzte Antwort falsch",
+        FilterExpression::Tag(_) => "Tag",
+        FilterExpression::Category(_) => "Fragekategorie",
+        FilterExpression::Section(_) => "Kapitel",
+        FilterExpression::Bin { .. } => "Lerntopf",
+        FilterExpression::CorrectCount { .. } => "Anzahl richtiger Antworten",
+        FilterExpression::WrongCount { .. } => "Anzahl falscher Antworten",
+        FilterExpression::DaysSinceLastAnswer { .. } => "Tage seit der letzten Antwort",
+        FilterExpression::Accuracy { .. } => "Anteil richtiger Antworten in %",
+    }
+}
+
+/// Returns a filter expression of each kind with default values, out of which
+/// the user chooses in the filter builder.
+fn get_filter_templates(section_paths: &[Vec<String>]) -> Vec<FilterExpression> {
+    vec![
+        FilterExpression::And(Vec::new()),
+        FilterExpression::Or(Vec::new()),
+        FilterExpression::Not(Box::new(FilterExpression::Marked)),
+        FilterExpression::Marked,
+        FilterExpression::New,
+        FilterExpression::LastAnswerWrong,
+        FilterExpression::Tag(String::new()),
+        FilterExpression::Category(Category::V),
+        FilterExpression::Section(section_paths.first().cloned().unwrap_or_default()),
+        FilterExpression::Bin {
+            comparison: Comparison::AtMost,
+            value: 1,
+        },
+        FilterExpression::CorrectCount {
+            comparison: Comparison::AtLeast,
+            value: 1,
+        },
+        FilterExpression::WrongCount {
+            comparison: Comparison::AtLeast,
+            value: 1,
+        },
+        FilterExpression::DaysSinceLastAnswer {
+            comparison: Comparison::AtMost,
+            value: 7,
+        },
+        FilterExpression::Accuracy {
+            comparison: Comparison::AtMost,
+            value: 50,
+        },
+    ]
+}
+
+/// Returns the description of the given filter expression which is shown to
+/// the user, e.g. "(markiert und Lerntopf höchstens 2)".
+fn describe_filter(expression: &FilterExpression) -> String {
+    let describe_all = |expressions: &[FilterExpression], separator: &str| {
+        let descriptions: Vec<String> = expressions.iter().map(describe_filter).collect();
+        if descriptions.len() == 1 {
+            descriptions[0].clone()
+        } else {
+            format!("({})", descriptions.join(separator))
+        }
+    };
+    match expression {
+        FilterExpression::And(expressions) if expressions.is_empty() => String::from("immer"),
+        FilterExpression::And(expressions) => describe_all(expressions, " und "),
+        FilterExpression::Or(expressions) if expressions.is_empty() => String::from("nie"),
+        FilterExpression::Or(expressions) => describe_all(expressions, " oder "),
+        FilterExpression::Not(expression) => format!("nicht {}", describe_filter(expression)),
+        FilterExpression::Marked => String::from("markiert"),
+        FilterExpression::New => String::from("noch nicht beantwortet"),
+        FilterExpression::LastAnswerWrong => String::from("letzte Antwort falsch"),
+        FilterExpression::Tag(tag) => format!("Tag '{}'", tag),
+        FilterExpression::Category(category) => format!("Kategorie {:?}", category),
+        FilterExpression::Section(section_path) => format!(
+            "Kapitel '{}'",
+            section_path.last().map_or("", String::as_str)
+        ),
+        FilterExpression::Bin { comparison, value } => {
+            format!("Lerntopf {} {}", get_comparison_name(*comparison), value)
+        }
+        FilterExpression::CorrectCount { comparison, value } => format!(
+            "{} {} richtige Antworten",
+            get_comparison_name(*comparison),
+            value
+        ),
+        FilterExpression::WrongCount { comparison, value } => format!(
+            "{} {} falsche Antworten",
+            get_comparison_name(*comparison),
+            value
+        ),
+        FilterExpression::DaysSinceLastAnswer { comparison, value } => format!(
+            "letzte Antwort vor {} {} Tagen",
+            get_comparison_name(*comparison),
+            value
+        ),
+        FilterExpression::Accuracy { comparison, value } => format!(
+            "{} {} % richtige Antworten",
+            get_comparison_name(*comparison),
+            value
+        ),
+    }
+}
+
+/// Shows and handles the Config's filter expression, which can be switched on
+/// and off and is edited with the filter builder.
+fn show_filter_settings(
+    ui: &mut Ui,
+    session: &mut Session,
+    section_paths: &[Vec<String>],
+    last_error: &mut Option<String>,
+) {
+    ui.label(
+        RichText::new(
+            "Fragen, die den Filter erfüllen, werden bevorzugt gestellt. Erfüllt keine \
+             Frage den Filter, wählt der Lernalgorithmus die nächste Frage.",
+        )
+        .font(FontId::proportional(10.0))
+        .color(Color32::DARK_GRAY),
+    );
+    let mut has_changed = false;
+    let mut is_active = session.config.filter.is_some();
+    if ui.checkbox(&mut is_active, "Filter aktiv").changed() {
+        session.config.filter = is_active
+            .then(|| FilterExpression::Or(vec![FilterExpression::Marked, FilterExpression::New]));
+        has_changed = true;
+    }
+    let tags = learning::get_all_tags(&session.learn_states);
+    if let Some(filter) = &mut session.config.filter {
+        has_changed |= show_filter_builder(
+            ui,
+            filter,
+            egui::Id::new("filter_builder"),
+            section_paths,
+            &tags,
+        );
+    }
+    if has_changed {
+        record_error(session.config.save(), last_error);
+    }
+}
+
+/// Shows the builder of the given filter expression, in which the expression's
+/// kind and values can be changed, and subexpressions can be added and removed.
+///
+/// ### Arguments
+/// * ui: The Ui in which the builder is shown
+/// * expression: The edited filter expression
+/// * id: A unique id of the expression's position in the filter expression tree
+/// * section_paths: All section paths of the catalog
+/// * tags: All tags which are used in the LearnStates
+///
+/// ### Return value
+/// * Whether or not the filter expression was changed
+fn show_filter_builder(
+    ui: &mut Ui,
+    expression: &mut FilterExpression,
+    id: egui::Id,
+    section_paths: &[Vec<String>],
+    tags: &[String],
+) -> bool {
+    let mut has_changed = false;
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(id.with("kind"))
+            .selected_text(get_filter_kind_name(expression))
+            .show_ui(ui, |ui| {
+                for template in get_filter_templates(section_paths) {
+                    let is_selected =
+                        std::mem::discriminant(expression) == std::mem::discriminant(&template);
+                    if ui
+                        .selectable_label(is_selected, get_filter_kind_name(&template))
+                        .clicked()
+                        && !is_selected
+                    {
+                        *expression = template;
+                        has_changed = true;
+                    }
+                }
+            });
+        match expression {
+            FilterExpression::Tag(tag) => {
+                has_changed |= ui
+                    .add(egui::TextEdit::singleline(tag).desired_width(120.0))
+                    .changed();
+                if !tags.is_empty() {
+                    ui.menu_button("Vorhandene Tags", |ui| {
+                        for existing_tag in tags {
+                            if ui.button(existing_tag).clicked() {
+                                *tag = existing_tag.clone();
+                                has_changed = true;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
+            }
+            FilterExpression::Category(category) => {
+                egui::ComboBox::from_id_source(id.with("category"))
+                    .selected_text(format!("{:?}", category))
+                    .show_ui(ui, |ui| {
+                        for other_category in Category::ALL {
+                            has_changed |= ui
+                                .selectable_value(
+                                    category,
+                                    other_category,
+                                    format!("{:?}", other_category),
+                                )
+                                .clicked();
+                        }
+                    });
+            }
+            FilterExpression::Section(section_path) => {
+                egui::ComboBox::from_id_source(id.with("section"))
+                    .selected_text(section_path.last().map_or("", String::as_str))
+                    .width(300.0)
+                    .show_ui(ui, |ui| {
+                        for other_section_path in section_paths {
+                            let title = format!(
+                                "{}{}",
+                                "   ".repeat(other_section_path.len() - 1),
+                                other_section_path.last().unwrap()
+                            );
+                            has_changed |= ui
+                                .selectable_value(section_path, other_section_path.clone(), title)
+                                .clicked();
+                        }
+                    });
+            }
+            FilterExpression::Bin { comparison, value }
+            | FilterExpression::CorrectCount { comparison, value }
+            | FilterExpression::WrongCount { comparison, value }
+            | FilterExpression::DaysSinceLastAnswer { comparison, value }
+            | FilterExpression::Accuracy { comparison, value } => {
+                egui::ComboBox::from_id_source(id.with("comparison"))
+                    .selected_text(get_comparison_name(*comparison))
+                    .show_ui(ui, |ui| {
+                        for other_comparison in Comparison::ALL {
+                            has_changed |= ui
+                                .selectable_value(
+                                    comparison,
+                                    other_comparison,
+                                    get_comparison_name(other_comparison),
+                                )
+                                .clicked();
+                        }
+                    });
+                has_changed |= ui.add(egui::DragValue::new(value)).changed();
+            }
+            _ => {}
+        }
+    });
+
+    // Subexpressions, indented below their parent
+    match expression {
+        FilterExpression::And(expressions) | FilterExpression::Or(expressions) => {
+            ui.indent(id.with("subexpressions"), |ui| {
+                let mut removed_index = None;
+                for (index, subexpression) in expressions.iter_mut().enumerate() {
+                    ui.group(|ui| {
+                        has_changed |= show_filter_builder(
+                            ui,
+                            subexpression,
+                            id.with(index),
+                            section_paths,
+                            tags,
+                        );
+                        if ui.small_button("Bedingung entfernen").clicked() {
+                            removed_index = Some(index);
+                        }
+                    });
+                }
+                if let Some(removed_index) = removed_index {
+                    expressions.remove(removed_index);
+                    has_changed = true;
+                }
+                if ui.small_button("Bedingung hinzufügen").clicked() {
+                    expressions.push(FilterExpression::Marked);
+                    has_changed = true;
+                }
+            });
+        }
+        FilterExpression::Not(subexpression) => {
+            ui.indent(id.with("subexpressions"), |ui| {
+                has_changed |=
+                    show_filter_builder(ui, subexpression, id.with(0), section_paths, tags);
+            });
+        }
+        _ => {}
+    }
+    has_changed
+}
+
+/// Shows and handles the tags of the current question: Each tag can be removed,
+/// and a new or an already used tag can be added.
+fn show_tags_editor(
+    ui: &mut Ui,
+    session: &mut Session,
+    new_tag: &mut String,
+    last_error: &mut Option<String>,
+) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Tags:");
+        let mut tags = session.get_tags().to_vec();
+        let mut has_changed = false;
+        let mut removed_index = None;
+        for (index, tag) in tags.iter().enumerate() {
+            if ui
+                .small_button(format!("{} ✕", tag))
+                .on_hover_text("Tag entfernen")
+                .clicked()
+            {
+                removed_index = Some(index);
+            }
+        }
+        if let Some(removed_index) = removed_index {
+            tags.remove(removed_index);
+            has_changed = true;
+        }
+
+        let new_tag_field = ui.add(
+            egui::TextEdit::singleline(new_tag)
+                .hint_text("Neuer Tag")
+                .desired_width(120.0),
+        );
+        // Enter adds the tag; It is consumed, so that it does not also act as
+        // the shortcut for the next question
+        let is_entered = new_tag_field.lost_focus()
+            && ui.input_mut(|input| input.consume_key(egui::Modifiers::NONE, egui::Key::Enter));
+        if (ui.small_button("Hinzufügen").clicked() || is_entered) && !new_tag.trim().is_empty() {
+            tags.push(new_tag.trim().to_string());
+            new_tag.clear();
+            has_changed = true;
+        }
+        let other_tags: Vec<String> = learning::get_all_tags(&session.learn_states)
+            .into_iter()
+            .filter(|tag| !tags.contains(tag))
+            .collect();
+        if !other_tags.is_empty() {
+            ui.menu_button("Vorhandene Tags", |ui| {
+                for tag in other_tags {
+                    if ui.button(&tag).clicked() {
+                        tags.push(tag);
+                        has_changed = true;
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+
+        if has_changed {
+            record_error(session.set_tags(&tags), last_error);
+        }
+    });
+}
+
+/// Shows the free-text search across all questions, with a results list in which
+/// each result can be practiced right away.
+///
+/// ### Return value
+/// * Whether or not the user chose a result to practice, which is then the
+///   session's current question
+fn show_search_view(ui: &mut Ui, search_view: &mut SearchView, session: &mut Session) -> bool {
+    ui.horizontal(|ui| {
+        ui.label("Suche:");
+        let response = ui.text_edit_singleline(&mut search_view.query);
+        if response.changed() {
+            search_view.results = search_view
+                .index
+                .search(&search_view.query, &session.learn_states);
+        }
+    });
+    ui.label(
+        RichText::new(
+            "Durchsucht Fragenummern, Fragen, Antworten und Notizen aller Fragen (unabhängig von Groß- \
+             und Kleinschreibung, Akzenten und Formelschreibweise).",
+        )
+        .font(FontId::proportional(10.0))
+        .color(Color32::DARK_GRAY),
+    );
+    if search_view.query.trim().is_empty() {
+        return false;
+    }
+    if search_view.results.len() > MAX_SEARCH_RESULTS {
+        ui.label(format!(
+            "{} Treffer, die ersten {} werden angezeigt:",
+            search_view.results.len(),
+            MAX_SEARCH_RESULTS
+        ));
+    } else {
+        ui.label(format!("{} Treffer", search_view.results.len()));
+    }
+
+    let mut chosen_identifier = None;
+    for result in search_view.results.iter().take(MAX_SEARCH_RESULTS) {
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Üben").clicked() {
+                chosen_identifier = Some(result.identifier.clone());
+            }
+            ui.label(RichText::new(&result.identifier).strong());
+            ui.label(format!("(Kategorie {:?})", result.category));
+        });
+        ui.label(&result.snippet);
+    }
+    match chosen_identifier {
+        Some(identifier) => session.practice_question(&identifier),
+        None => false,
+    }
+}
+
+/// Returns the name of the given bin filter as shown in the catalog view.
+fn get_bin_filter_name(bin_filter: BinFilter) -> String {
+    match bin_filter {
+        BinFilter::All => String::from("Alle"),
+        BinFilter::Unanswered => String::from("Noch nicht beantwortet"),
+        BinFilter::Bin(bin) => format!("Lerntopf '{}'", bin),
+    }
+}
+
+/// Returns the summary of the given LearnState (or of a question without one)
+/// which is shown in the catalog view, e.g. "Lerntopf '2', 3x richtig, 1x falsch".
+fn get_learn_state_summary(learn_state: Option<&LearnState>) -> String {
+    let mut parts = Vec::new();
+    match learn_state {
+        Some(learn_state) if learn_state.correct + learn_state.wrong > 0 => {
+            parts.push(format!("Lerntopf '{}'", learn_state.current_bin));
+            parts.push(format!("{}x richtig", learn_state.correct));
+            parts.push(format!("{}x falsch", learn_state.wrong));
+        }
+        _ => parts.push(String::from("Noch nicht beantwortet")),
+    }
+    if let Some(learn_state) = learn_state {
+        if learn_state.marked {
+            parts.push(String::from("markiert"));
+        }
+        if !learn_state.tags.is_empty() {
+            parts.push(format!("Tags: {}", learn_state.tags.join(", ")));
+        }
+    }
+    parts.join(", ")
+}
+
+/// Returns the title of the given catalog section as shown in the catalog view.
+/// The outermost section is left out, as it only names the exam part.
+fn get_section_title(section_path: &[String]) -> String {
+    match section_path.len() {
+        0 => String::from("Ohne Kapitel"),
+        1 => section_path[0].clone(),
+        _ => section_path[1..].join(" › "),
+    }
+}
+
+/// Shows the catalog browser, i.e. all questions which pass the filters grouped
+/// by category and section, each with its pictures, its answers (the correct one
+/// highlighted) and its learning progress.
+///
+/// ### Return value
+/// * Whether or not the user chose a question to practice, which is then the
+///   session's current question
+fn show_catalog_view(
+    ui: &mut Ui,
+    catalog_view: &mut CatalogView,
+    session: &mut Session,
+    picture_index: &PictureIndex,
+) -> bool {
+    // Filters; Besides the configured bins, all bins which contain questions
+    // can be chosen
+    let mut bins: Vec<u64> = (0..=session.config.max_learn_bin)
+        .chain(
+            session
+                .learn_states
+                .values()
+                .map(|learn_state| learn_state.current_bin),
+        )
+        .collect();
+    bins.sort_unstable();
+    bins.dedup();
+    ui.horizontal(|ui| {
+        ui.label("Lerntopf:");
+        egui::ComboBox::from_id_source("catalog_bin_filter")
+            .selected_text(get_bin_filter_name(catalog_view.bin_filter))
+            .show_ui(ui, |ui| {
+                let bin_filters = [BinFilter::All, BinFilter::Unanswered]
+                    .into_iter()
+                    .chain(bins.into_iter().map(BinFilter::Bin));
+                for bin_filter in bin_filters {
+                    ui.selectable_value(
+                        &mut catalog_view.bin_filter,
+                        bin_filter,
+                        get_bin_filter_name(bin_filter),
+                    );
+                }
+            });
+        ui.checkbox(&mut catalog_view.only_marked, "Nur markierte Fragen");
+    });
+
+    let questions: Vec<&Question> = session
+        .get_questions()
+        .iter()
+        .filter(|question| catalog_view.is_shown(session.learn_states.get(&question.identifier)))
+        .collect();
+    ui.label(format!(
+        "{} von {} Fragen",
+        questions.len(),
+        session.get_questions().len()
+    ));
+
+    let mut chosen_identifier = None;
+    for category in Category::ALL {
+        // The sections of the category's questions in catalog order
+        let mut sections: Vec<(&[String], Vec<&Question>)> = Vec::new();
+        for question in questions
+            .iter()
+            .filter(|question| question.category == category)
+        {
+            match sections
+                .iter_mut()
+                .find(|(section_path, _)| *section_path == question.section_path.as_slice())
+            {
+                Some((_, section_questions)) => section_questions.push(question),
+                None => sections.push((&question.section_path, vec![question])),
+            }
+        }
+        if sections.is_empty() {
+            continue;
+        }
+        let question_count: usize = sections.iter().map(|(_, questions)| questions.len()).sum();
+        egui::CollapsingHeader::new(format!(
+            "Kategorie {:?} ({} Fragen)",
+            category, question_count
+        ))
+        .id_source(("catalog_category", format!("{:?}", category)))
+        .show(ui, |ui| {
+            for (section_path, section_questions) in &sections {
+                egui::CollapsingHeader::new(format!(
+                    "{} ({} Fragen)",
+                    get_section_title(section_path),
+                    section_questions.len()
+                ))
+                .id_source(("catalog_section", format!("{:?}", category), section_path))
+                .show(ui, |ui| {
+                    for question in section_questions {
+                        let learn_state = session.learn_states.get(&question.identifier);
+                        if show_catalog_question(ui, question, learn_state, picture_index) {
+                            chosen_identifier = Some(question.identifier.clone());
+                        }
+                    }
+                });
+            }
+        });
+    }
+    match chosen_identifier {
+        Some(identifier) => session.practice_question(&identifier),
+        None => false,
+    }
+}
+
+/// Shows a single question of the catalog view with its learning progress, its
+/// pictures and all answers, whereby the correct answer is highlighted.
+///
+/// ### Return value
+/// * Whether or not the user chose to practice the question
+fn show_catalog_question(
+    ui: &mut Ui,
+    question: &Question,
+    learn_state: Option<&LearnState>,
+    picture_index: &PictureIndex,
+) -> bool {
+    ui.separator();
+    let is_practice_clicked = ui
+        .horizontal(|ui| {
+            ui.label(RichText::new(&question.identifier).strong());
+            ui.label(get_learn_state_summary(learn_state));
+            ui.button("Üben").clicked()
+        })
+        .inner;
+    if !question.picture_question.is_empty() {
+        show_picture(ui, picture_index, &question.picture_question);
+    }
+    show_text_with_math(ui, &question.question);
+    if let Some(learn_state) = learn_state.filter(|learn_state| !learn_state.note.is_empty()) {
+        ui.label(RichText::new(format!("Notiz: {}", learn_state.note)).italics());
+    }
+    for answer in [Answer::A, Answer::B, Answer::C, Answer::D] {
+        // In the catalog, answer A is always the correct one
+        let mut frame = egui::Frame::group(ui.style());
+        if answer == Answer::A {
+            frame = frame.stroke(Stroke::new(2.0, Color32::DARK_GREEN));
+        }
+        frame.show(ui, |ui| {
+            if answer == Answer::A {
+                ui.label(
+                    RichText::new("Richtige Antwort:")
+                        .strong()
+                        .color(Color32::DARK_GREEN),
+                );
+            }
+            show_text_with_math(ui, &learning::get_answer_text(question, answer));
+            let picture = learning::get_answer_picture(question, answer);
+            if !picture.is_empty() {
+                show_picture(ui, picture_index, &picture);
+            }
+        });
+    }
+    is_practice_clicked
+}
+
+/// Shows the exam simulation, i.e. the exam part selection, the running exam or
+/// the result of the last exam.
+///
+/// If the user applies an exam result to the learning progress, an error while
+/// recording or saving the exam's answers is stored in last_error.
+fn show_exam_view(
+    ui: &mut Ui,
+    exam_view: &mut ExamView,
+    session: &mut Session,
+    picture_index: &PictureIndex,
+    last_error: &mut Option<String>,
+) {
+    // Exam part selection (only if no exam is running)
+    let is_running = exam_view.exam.is_some() && exam_view.result.is_none();
+    if !is_running {
+        ui.horizontal(|ui| {
+            ui.label("Prüfungsteil:");
+            egui::ComboBox::from_id_source("exam_part")
+                .selected_text(exam_view.part.get_name())
+                .show_ui(ui, |ui| {
+                    for part in ExamPart::ALL {
+                        ui.selectable_value(&mut exam_view.part, part, part.get_name());
+                    }
+                });
+            if ui.button("Prüfung starten").clicked() {
+                exam_view.exam = Some(session.create_exam(exam_view.part));
+                exam_view.result = None;
+                exam_view.has_applied_result = false;
+            }
+        });
+        ui.label(format!(
+            "{} Fragen, {} Minuten, bestanden ab {} richtigen Antworten",
+            exam_view.part.get_question_count(),
+            exam_view.part.get_duration_in_sec() / 60,
+            exam_view.part.get_pass_count()
+        ));
+    }
+
+    let Some(exam) = &mut exam_view.exam else {
+        return;
+    };
+    ui.separator();
+
+    // Result of the submitted exam
+    if let Some(result) = &exam_view.result {
+        ui.heading(format!("Ergebnis: {}", result.part.get_name()));
+        if result.passed {
+            ui.label(
+                RichText::new("Bestanden!")
+                    .strong()
+                    .color(Color32::DARK_GREEN),
+            );
+        } else {
+            ui.label(
+                RichText::new("Nicht bestanden")
+                    .strong()
+                    .color(Color32::RED),
+            );
+        }
+        ui.label(format!(
+            "Korrekt beantwortete Fragen: {} von {} ({} %), benötigt: {}",
+            result.correct,
+            result.question_results.len(),
+            result.get_correct_percentage(),
+            result.part.get_pass_count()
+        ));
+        ui.label(format!("Falsch beantwortete Fragen: {}", result.wrong));
+        ui.label(format!("Nicht beantwortete Fragen: {}", result.unanswered));
+        ui.label(format!(
+            "Benötigte Zeit: {}",
+            format_duration(result.duration_in_sec)
+        ));
+        let apply_button = ui.add_enabled(
+            !exam_view.has_applied_result,
+            egui::Button::new("Ergebnis in den Lernfortschritt übernehmen"),
+        );
+        if apply_button.clicked() {
+            record_error(session.apply_exam_result(result), last_error);
+            exam_view.has_applied_result = true;
+        }
+
+        for (index, (print_question, given_answer)) in
+            exam.questions.iter().zip(&exam.given_answers).enumerate()
+        {
+            ui.separator();
+            let is_correct = result.question_results[index].is_correct;
+            let status = match given_answer {
+                Some(given_index) if is_correct => {
+                    RichText::new(format!("Richtig ({})", ANSWER_NAMES[*given_index]))
+                        .color(Color32::DARK_GREEN)
+                }
+                Some(given_index) => RichText::new(format!(
+                    "Falsch ({}), richtige Antwort ist {:?}",
+                    ANSWER_NAMES[*given_index],
+                    print_question.get_correct_answer()
+                ))
+                .color(Color32::RED),
+                None => RichText::new(format!(
+                    "Nicht beantwortet, richtige Antwort ist {:?}",
+                    print_question.get_correct_answer()
+                ))
+                .color(Color32::RED),
+            };
+            ui.label(
+                RichText::new(format!(
+                    "{}. {}",
+                    index + 1,
+                    print_question.question.identifier
+                ))
+                .strong(),
+            );
+            show_text_with_math(ui, &print_question.question.question);
+            ui.label(status);
+        }
+        return;
+    }
+
+    // Running exam
+    let remaining_sec = exam.get_remaining_sec();
+    ui.label(
+        RichText::new(format!(
+            "{}: Verbleibende Zeit {}, beantwortet {} von {}",
+            exam.part.get_name(),
+            format_duration(remaining_sec),
+            exam.get_answered_count(),
+            exam.questions.len()
+        ))
+        .strong(),
+    );
+    for (index, print_question) in exam.questions.iter().enumerate() {
+        ui.separator();
+        ui.label(
+            RichText::new(format!(
+                "{}. {}",
+                index + 1,
+                print_question.question.identifier
+            ))
+            .strong(),
+        );
+        if !print_question.question.picture_question.is_empty() {
+            show_picture(ui, picture_index, &print_question.question.picture_question);
+        }
+        show_text_with_math(ui, &print_question.question.question);
+        if !print_question.question.picture_a.is_empty() {
+            ui.horizontal(|ui| {
+                for (answer_index, answer_name) in ANSWER_NAMES.iter().enumerate() {
+                    ui.label(format!("{}:", answer_name));
+                    show_picture(
+                        ui,
+                        picture_index,
+                        &print_question.get_shuffled_picture(answer_index),
+                    );
+                }
+            });
+        }
+        for (answer_index, answer_name) in ANSWER_NAMES.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.radio_value(
+                    &mut exam.given_answers[index],
+                    Some(answer_index),
+                    *answer_name,
+                );
+                show_text_with_math(ui, &print_question.get_shuffled_answer(answer_index));
+            });
+        }
+    }
+    ui.separator();
+    // The exam is submitted by the user or, at the latest, when the time is over
+    if ui.button("Prüfung abgeben").clicked() || exam.is_time_over() {
+        exam_view.result = Some(exam.submit());
+    } else {
+        ui.ctx().request_repaint_after(Duration::from_secs(1));
+    }
+}
+
+/// Appends the given formula node to the given LayoutJob.
+///
+/// Superscripts and subscripts are appended with a smaller font which is aligned
+/// at the top or bottom of the text row. Fractions and roots cannot be typeset
+/// inside a LayoutJob, so that their Unicode representation is appended instead
+/// (this only happens if they are nested into a superscript or subscript).
+fn append_math_node(
+    job: &mut LayoutJob,
+    node: &MathNode,
+    font_size: f32,
+    valign: Align,
+    color: Color32,
+) {
+    let text_format = |italics: bool| TextFormat {
+        font_id: FontId::proportional(font_size),
+        color,
+        italics,
+        valign,
+        ..Default::default()
+    };
+    match node {
+        MathNode::Symbol(text) => job.append(text, 0.0, text_format(false)),
+        MathNode::Variable(text) => job.append(text, 0.0, text_format(true)),
+        MathNode::Superscript(inner) => {
+            for inner_node in inner {
+                append_math_node(
+                    job,
+                    inner_node,
+                    font_size * SCRIPT_FONT_FACTOR,
+                    Align::TOP,
+                    color,
+                );
+            }
+        }
+        MathNode::Subscript(inner) => {
+            for inner_node in inner {
+                append_math_node(
+                    job,
+                    inner_node,
+                    font_size * SCRIPT_FONT_FACTOR,
+                    Align::BOTTOM,
+                    color,
+                );
+            }
+        }
+        MathNode::Fraction(_, _) | MathNode::Root(_) => job.append(
+            &math::nodes_to_unicode(std::slice::from_ref(node)),
+            0.0,
+            text_format(false),
+        ),
+    }
+}
+
+/// Returns a LayoutJob with the given formula nodes.
+fn get_math_layout_job(nodes: &[MathNode], color: Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    for node in nodes {
+        append_math_node(&mut job, node, TEXT_FONT_SIZE, Align::BOTTOM, color);
+    }
+    job
+}
+
+/// Shows the given LayoutJob as label (if it is not empty) and resets it.
+fn flush_layout_job(ui: &mut Ui, job: &mut LayoutJob) {
+    if !job.sections.is_empty() {
+        ui.label(std::mem::take(job));
+    }
+}
+
+/// Shows a fraction with its numerator above its denominator.
+fn show_fraction(ui: &mut Ui, numerator: &[MathNode], denominator: &[MathNode], color: Color32) {
+    let numerator_galley =
+        ui.fonts(|fonts| fonts.layout_job(get_math_layout_job(numerator, color)));
+    let denominator_galley =
+        ui.fonts(|fonts| fonts.layout_job(get_math_layout_job(denominator, color)));
+    let width = numerator_galley.size().x.max(denominator_galley.size().x) + 4.0;
+    let height = numerator_galley.size().y + denominator_galley.size().y + 3.0;
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(width, height), Sense::hover());
+
+    let line_y = rect.top() + numerator_galley.size().y + 1.5;
+    let numerator_x = rect.center().x - numerator_galley.size().x / 2.0;
+    let denominator_x = rect.center().x - denominator_galley.size().x / 2.0;
+    ui.painter()
+        .galley(egui::pos2(numerator_x, rect.top()), numerator_galley, color);
+    ui.painter().galley(
+        egui::pos2(denominator_x, line_y + 1.5),
+        denominator_galley,
+        color,
+    );
+    ui.painter()
+        .hline(rect.x_range().shrink(1.0), line_y, Stroke::new(1.0, color));
+}
+
+/// Shows a square root sign with a line over the given radicand.
+fn show_root(ui: &mut Ui, radicand: &[MathNode], color: Color32) {
+    let sign_galley = ui.fonts(|fonts| {
+        fonts.layout_no_wrap(
+            String::from("√"),
+            FontId::proportional(TEXT_FONT_SIZE),
+            color,
+        )
+    });
+    let radicand_galley = ui.fonts(|fonts| fonts.layout_job(get_math_layout_job(radicand, color)));
+    let width = sign_galley.size().x + radicand_galley.size().x + 2.0;
+    let height = sign_galley.size().y.max(radicand_galley.size().y) + 2.0;
+    let (rect, _) = ui.allocate_exact_size(Vec2::new(width, height), Sense::hover());
+
+    let radicand_x = rect.left() + sign_galley.size().x;
+    ui.painter().galley(
+        egui::pos2(rect.left(), rect.top() + 2.0),
+        sign_galley,
+        color,
+    );
+    ui.painter().galley(
+        egui::pos2(radicand_x, rect.top() + 2.0),
+        radicand_galley,
+        color,
+    );
+    ui.painter().hline(
+        radicand_x..=rect.right(),
+        rect.top() + 1.0,
+        Stroke::new(1.0, color),
+    );
+}
+
+/// Shows the picture with the given identifier, or a note if its file is missing.
+fn show_picture(ui: &mut Ui, picture_index: &PictureIndex, picture: &str) {
+    match picture_index.get_uri(picture) {
+        Some(uri) => {
+            ui.add(
+                egui::Image::new(uri)
+                    .fit_to_exact_size(Vec2::new(MAX_IMAGE_WIDTH, MAX_IMAGE_HEIGHT))
+                    .maintain_aspect_ratio(true)
+                    .bg_fill(Color32::DARK_GRAY),
+            );
+        }
+        None => {
+            ui.label(RichText::new(format!("Bild '{}' fehlt", picture)).color(Color32::RED));
+        }
+    }
+}
+
+/// Shows the given question or answer text, whereby the LaTeX formulas in it
+/// are typeset (see the math module).
+fn show_text_with_math(ui: &mut Ui, text: &str) {
+    if !math::contains_math(text) {
+        // Text commands (e.g. "\milliOhm") are replaced in formula-free texts, too
+        ui.label(math::to_unicode(text));
+        return;
+    }
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        let color = ui.visuals().text_color();
+        let mut job = LayoutJob::default();
+        for part in math::parse_text(text) {
+            match part {
+                TextPart::Text(plain_text) => job.append(
+                    &plain_text,
+                    0.0,
+                    TextFormat::simple(FontId::proportional(TEXT_FONT_SIZE), color),
+                ),
+                TextPart::Math(nodes) => {
+                    for node in &nodes {
+                        match node {
+                            MathNode::Fraction(numerator, denominator) => {
+                                flush_layout_job(ui, &mut job);
+                                show_fraction(ui, numerator, denominator, color);
+                            }
+                            MathNode::Root(radicand) => {
+                                flush_layout_job(ui, &mut job);
+                                show_root(ui, radicand, color);
+                            }
+                            _ => append_math_node(
+                                &mut job,
+                                node,
+                                TEXT_FONT_SIZE,
+                                Align::BOTTOM,
+                                color,
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+        flush_layout_job(ui, &mut job);
+    });
+}
+
+// PUBLIC FUNCTIONS SECTION //
+
+/// Runs a small window which explains the given startup error and lets the user
+/// choose how to proceed.
+///
+/// ### Arguments
+/// * error: The error which prevents Funkfragenhelfer from starting
+/// * actions: The actions which are offered besides quitting (e.g. repairing
+///   or resetting the affected file)
+///
+/// ### Return value
+/// * The chosen action, or ErrorAction::Quit if the window was closed or could
+///   not be opened
+pub fn run_error_screen(error: &FfhError, actions: &[ErrorAction]) -> ErrorAction {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([600.0, 300.0]),
+        ..Default::default()
+    };
+    let message = error.to_string();
+    let actions = actions.to_vec();
+    let chosen_action = Rc::new(Cell::new(ErrorAction::Quit));
+    let chosen_action_in_gui = Rc::clone(&chosen_action);
+
+    let result = eframe::run_simple_native(
+        "Funkfragenhelfer - Fehler",
+        options,
+        move |ctx, _frame| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.heading("Der Funkfragenhelfer kann nicht gestartet werden");
+                ui.separator();
+                ui.label(RichText::new(&message).color(Color32::RED));
+                ui.separator();
+                let mut clicked_action = None;
+                for action in &actions {
+                    let (button_text, explanation) = match action {
+                        ErrorAction::Repair => (
+                            "Reparieren",
+                            "Fehlende oder unlesbare Einträge werden durch Standardwerte ersetzt, \
+                         alle lesbaren Einträge bleiben erhalten.",
+                        ),
+                        ErrorAction::Reset => (
+                            "Zurücksetzen",
+                            "Die Datei wird vollständig auf die Standardwerte zurückgesetzt.",
+                        ),
+                        ErrorAction::Rebuild => (
+                            "Aus Antworthistorie wiederherstellen",
+                            "Der Lernfortschritt wird aus allen bisherigen Antworten neu berechnet; \
+                         Markierungen, Notizen und Tags werden aus der neuesten Sicherung übernommen.",
+                        ),
+                        ErrorAction::Quit => ("Beenden", ""),
+                    };
+                    ui.horizontal(|ui| {
+                        if ui.button(button_text).clicked() {
+                            clicked_action = Some(*action);
+                        }
+                        ui.label(explanation);
+                    });
+                }
+                if !actions.is_empty() {
+                    ui.label(
+                        "In allen Fällen wird die ursprüngliche Datei mit der Endung \
+                          \".corrupt\" aufbewahrt.",
+                    );
+                }
+                if ui.button("Beenden").clicked() {
+                    clicked_action = Some(ErrorAction::Quit);
+                }
+                if let Some(action) = clicked_action {
+                    chosen_action_in_gui.set(action);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+        },
+    );
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        return ErrorAction::Quit;
+    }
+    chosen_action.get()
+}
+
+/// Runs the profile picker, in which the user chooses the profile to learn
+/// with and can create, rename and delete profiles (see the profiles module).
+///
+/// ### Arguments
+/// * data_dir: The data dir which contains the profiles
+///
+/// ### Return value
+/// * The name of the chosen profile, or None if the window was closed or
+///   could not be opened
+pub fn run_profile_picker(data_dir: &str) -> Option<String> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([600.0, 500.0]),
+        ..Default::default()
+    };
+    let data_dir = data_dir.to_string();
+    let chosen_profile: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    let chosen_profile_in_gui = Rc::clone(&chosen_profile);
+
+    // Picker state
+    let mut new_profile_name = String::new();
+    // The profile which is currently renamed, together with its new name
+    let mut renamed_profile: Option<(String, String)> = None;
+    // The profile whose deletion has yet to be confirmed
+    let mut deleted_profile: Option<String> = None;
+    let mut last_error: Option<String> = None;
+
+    let result =
+        eframe::run_simple_native("Funkfragenhelfer - Profile", options, move |ctx, _frame| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.heading("Wer lernt?");
+                ui.separator();
+                let profile_names = match profiles::get_profile_names(&data_dir) {
+                    Ok(profile_names) => profile_names,
+                    Err(e) => {
+                        ui.label(RichText::new(e.to_string()).color(Color32::RED));
+                        return;
+                    }
+                };
+
+                for profile_name in &profile_names {
+                    ui.horizontal(|ui| {
+                        // Renaming of the profile
+                        if let Some((renamed_name, new_name)) = &mut renamed_profile {
+                            if renamed_name == profile_name {
+                                ui.text_edit_singleline(new_name);
+                                if ui.button("Speichern").clicked() {
+                                    let result =
+                                        profiles::check_new_profile_name(&data_dir, new_name)
+                                            .and_then(|_| {
+                                                profiles::rename_profile(
+                                                    &data_dir,
+                                                    profile_name,
+                                                    new_name,
+                                                )
+                                                .map_err(|e| e.to_string())
+                                            });
+                                    match result {
+                                        Ok(_) => {
+                                            renamed_profile = None;
+                                            last_error = None;
+                                        }
+                                        Err(e) => last_error = Some(e),
+                                    }
+                                }
+                                if ui.button("Abbrechen").clicked() {
+                                    renamed_profile = None;
+                                }
+                                return;
+                            }
+                        }
+
+                        // Deletion of the profile, which has to be confirmed
+                        if deleted_profile.as_ref() == Some(profile_name) {
+                            ui.label(format!(
+                                "Profil '{}' mit dem gesamten Lernfortschritt löschen?",
+                                profile_name
+                            ));
+                            if ui.button("Ja, löschen").clicked() {
+                                record_error(
+                                    profiles::delete_profile(&data_dir, profile_name),
+                                    &mut last_error,
+                                );
+                                deleted_profile = None;
+                            }
+                            if ui.button("Abbrechen").clicked() {
+                                deleted_profile = None;
+                            }
+                            return;
+                        }
+
+                        if ui.button(RichText::new(profile_name).strong()).clicked() {
+                            *chosen_profile_in_gui.borrow_mut() = Some(profile_name.clone());
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                        // The default profile uses the data dir itself, so it can
+                        // neither be renamed nor deleted
+                        if profile_name != profiles::DEFAULT_PROFILE_NAME {
+                            if ui.small_button("Umbenennen").clicked() {
+                                renamed_profile =
+                                    Some((profile_name.clone(), profile_name.clone()));
+                            }
+                            if ui.small_button("Löschen").clicked() {
+                                deleted_profile = Some(profile_name.clone());
+                            }
+                        }
+                    });
+                }
+
+                // Creation of a new profile
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Neues Profil:");
+                    ui.text_edit_singleline(&mut new_profile_name);
+                    if ui.button("Anlegen").clicked() {
+                        let result = profiles::check_new_profile_name(&data_dir, &new_profile_name)
+                            .and_then(|_| {
+                                profiles::create_profile(&data_dir, &new_profile_name)
+                                    .map_err(|e| e.to_string())
+                            });
+                        match result {
+                            Ok(_) => {
+                                new_profile_name.clear();
+                                last_error = None;
+                            }
+                            Err(e) => last_error = Some(e),
+                        }
+                    }
+                });
+                if let Some(error) = &last_error {
+                    ui.label(RichText::new(error).color(Color32::RED));
+                }
+            });
+        });
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        return None;
+    }
+    let chosen_profile = chosen_profile.borrow().clone();
+    chosen_profile
+}
 
 /// Runs the Funkfragenhelfer GUI
 ///
 /// Here, the full GUI and all its actions are defined.
 ///
 /// ### Arguments
-/// * config: The current Funkfragenhelfer configuration
-/// * learn_states: The current question LearnStates
-/// * questions: The set of all questions that can be asked
+/// * session: The learning session, which contains the Config, the LearnStates
+///   and all questions that can be asked
+/// * catalog_metadata: The metadata of the loaded question catalog
+/// * picture_index: The index of all question and answer picture files
+/// * warnings: Warnings about restored files which are shown to the user
+/// * profile_name: The name of the current profile (see the profiles module)
+///
+/// ### Return value
+/// * How the GUI was left, or an eframe::Error if it could not be run
 pub fn run(
-    mut config: Config,
-    mut learn_states: LearnStates,
-    questions: Vec<question::Question>,
-) -> Result<(), eframe::Error> {
+    mut session: Session,
+    catalog_metadata: CatalogMetadata,
+    picture_index: PictureIndex,
+    warnings: Vec<String>,
+    profile_name: String,
+) -> Result<GuiExit, eframe::Error> {
     // Set the egui options
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([800.0, 1000.0]),
         ..Default::default()
     };
 
-    // Application state
-    let mut has_answered = false;
-    let mut has_answered_first = false;
-    let mut eligible_questions = question::get_eligible_questions(&questions, &config);
-    let mut given_answer: usize = 0;
-    let mut print_question =
-        learning::get_next_print_question(&eligible_questions, &mut learn_states, &config);
-    let mut correct_answers_since_start = 0;
-    let mut answers_since_start = 0;
-    let mut statistics = Statistics::new(&eligible_questions, &learn_states);
+    // Application state (besides the learning session)
+    let mut last_error: Option<String> = None;
+    // The last message of the session (e.g. about a deactivated study deck)
+    let mut session_message: Option<String> = None;
+    let gui_exit = Rc::new(Cell::new(GuiExit::Quit));
+    let gui_exit_in_gui = Rc::clone(&gui_exit);
+
+    let section_paths = question::get_section_paths(session.get_questions());
+    let mut view = View::Practice;
+    let mut search_view = SearchView {
+        index: SearchIndex::new(session.get_questions()),
+        query: String::new(),
+        results: Vec::new(),
+    };
+    let mut deck_editor = DeckEditor::new(Deck::new(""));
+    let mut new_tag = String::new();
+    let mut catalog_view = CatalogView {
+        bin_filter: BinFilter::All,
+        only_marked: false,
+    };
+    let mut exam_view = ExamView {
+        part: ExamPart::TechnikN,
+        exam: None,
+        result: None,
+        has_applied_result: false,
+    };
 
     let index_name_tuples = vec![(0, "A"), (1, "B"), (2, "C"), (3, "D")];
 
     // GUI main run loop; We use "run_simple_native" as the simplest
     // egui wrapper available.
-    eframe::run_simple_native("Funkfragenhelfer V 0.3.0", options, move |ctx, _frame| {
-        // Image loader do teh question images; Can also load SVGs
-        egui_extras::install_image_loaders(ctx);
+    let result =
+        eframe::run_simple_native("Funkfragenhelfer V 0.3.0", options, move |ctx, _frame| {
+            // Image loader do teh question images; Can also load SVGs
+            egui_extras::install_image_loaders(ctx);
 
-        // Central widget which includes all other widhets
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::both().show(ui, |ui| {
-                let mut answer_text = String::from("Bild");
+            // Central widget which includes all other widhets
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::ScrollArea::both().show(ui, |ui| {
+                    let mut answer_text = String::from("Bild");
 
-                // Show question data source
-                ui.label(RichText::new("Bereitsteller der Fragen: Bundesnetzagentur, \
+                    // Show question data source
+                    ui.label(
+                        RichText::new(format!(
+                            "Bereitsteller der Fragen: Bundesnetzagentur, \
                                                    Datensatz: 'Prüfungsfragen zum \
                                                    Erwerb von Amateurfunkprüfungsbescheinigungen, \
-                                                   3. Auflage, März 2024', Lizenz der Fragen: dl-de/by-2-0"
-                ).font(FontId::proportional(10.0)).color(Color32::DARK_GRAY));
+                                                   {}', Lizenz der Fragen: dl-de/by-2-0",
+                            catalog_metadata.edition
+                        ))
+                        .font(FontId::proportional(10.0))
+                        .color(Color32::DARK_GRAY),
+                    );
 
-                // Show and handle question categories
-                ui.horizontal(|ui| {
-                    ui.label("Fragekategorien:");
-                    let mut update_config = |config: &mut Config| {
-                        config.save();
-                        eligible_questions = question::get_eligible_questions(&questions, config);
-                        statistics = Statistics::new(&eligible_questions, &learn_states);
-                    };
-                    if ui.checkbox(&mut config.include_v, "V").changed() {
-                        if config.all_includes_false() {
-                            config.include_v = true;
+                    // Show the current profile and allow to switch to another one
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Profil: {}", profile_name));
+                        if ui.small_button("Profil wechseln").clicked() {
+                            gui_exit_in_gui.set(GuiExit::SwitchProfile);
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                         }
-                        update_config(&mut config);
+                    });
+
+                    // Show warnings about restored files (see the persistence module),
+                    // the last message of the session and the last error while saving
+                    for warning in &warnings {
+                        ui.label(RichText::new(warning).strong().color(Color32::RED));
                     }
-                    if ui.checkbox(&mut config.include_b, "B").changed() {
-                        if config.all_includes_false() {
-                            config.include_b = true;
-                        }
-                        update_config(&mut config);
+                    if let Some(message) = session.take_message() {
+                        session_message = Some(message);
                     }
-                    if ui.checkbox(&mut config.include_n, "N").changed() {
-                        if config.all_includes_false() {
-                            config.include_n = true;
-                        }
-                        update_config(&mut config);
+                    if let Some(message) = &session_message {
+                        ui.label(RichText::new(message).strong().color(Color32::RED));
                     }
-                    if ui.checkbox(&mut config.include_e, "E").changed() {
-                        if config.all_includes_false() {
-                            config.include_e = true;
-                        }
-                        update_config(&mut config);
+                    if let Some(error) = &last_error {
+                        ui.label(
+                            RichText::new(format!("Fehler beim Speichern: {}", error))
+                                .strong()
+                                .color(Color32::RED),
+                        );
                     }
-                    if ui.checkbox(&mut config.include_a, "A").changed() {
-                        if config.all_includes_false() {
-                            config.include_a = true;
+
+                    // Show and handle the mode selection (practice or exam simulation)
+                    ui.horizontal(|ui| {
+                        ui.label("Modus:");
+                        ui.selectable_value(&mut view, View::Practice, "Üben");
+                        ui.selectable_value(&mut view, View::Exam, "Prüfungssimulation");
+                        ui.selectable_value(&mut view, View::Confusions, "Verwechslungen");
+                        ui.selectable_value(&mut view, View::Search, "Suche");
+                        ui.selectable_value(&mut view, View::Catalog, "Katalog");
+                    });
+                    if view == View::Catalog {
+                        if show_catalog_view(ui, &mut catalog_view, &mut session, &picture_index) {
+                            view = View::Practice;
                         }
-                        update_config(&mut config);
+                        return;
                     }
-                });
-
-                // Show and handle question filters
-                ui.horizontal(|ui| {
-                    ui.label("Filter (falls anwendbar):");
-                    if ui.checkbox(&mut config.prefer_marked, "Markierte Fragen").changed() {
-                        config.save();
+                    if view == View::Search {
+                        if show_search_view(ui, &mut search_view, &mut session) {
+                            view = View::Practice;
+                        }
+                        return;
                     }
-                    if ui.checkbox(&mut config.prefer_wrong, "Falsch beantwortete Fragen").changed() {
-                        config.save();
+                    if view == View::Confusions {
+                        show_confusions_view(
+                            ui,
+                            session.get_eligible_questions(),
+                            &picture_index,
+                            &session.learn_states,
+                        );
+                        return;
                     }
-                    if ui.checkbox(&mut config.prefer_new, "Noch nicht beantwortete Fragen").changed() {
-                        config.save();
+                    if view == View::Exam {
+                        show_exam_view(
+                            ui,
+                            &mut exam_view,
+                            &mut session,
+                            &picture_index,
+                            &mut last_error,
+                        );
+                        return;
                     }
-                });
 
-                // Show picture question if one exists. Here, it is also made sure that such
-                // a picture really exists as there seem to be some wrong picture associations
-                // in the Bundesnetzagentur dataset.
-                ui.separator();
-                if !print_question.question.picture_question.is_empty() {
-                    let pathstr = format!(
-                        "file://resources/fragenkatalog/svgs/{}.svg",
-                        print_question.question.picture_question
-                    );
-                    let relpath = format!(
-                        "./resources/fragenkatalog/svgs/{}.svg",
-                        print_question.question.picture_question
-                    );
-                    let path = Path::new(&relpath);
-
-                    if path.exists() {
-                        ui.add(
-                            egui::Image::new(pathstr)
-                                .fit_to_exact_size(Vec2::new(MAX_IMAGE_WIDTH, MAX_IMAGE_HEIGHT))
-                                .maintain_aspect_ratio(true)
-                                .bg_fill(Color32::DARK_GRAY),
+                    // Show and handle the study decks (see the deck module)
+                    show_deck_selection(ui, &mut session, &mut last_error);
+                    egui::CollapsingHeader::new("Lernstapel verwalten").show(ui, |ui| {
+                        show_deck_management(
+                            ui,
+                            &mut deck_editor,
+                            &mut session,
+                            &section_paths,
+                            &mut last_error,
                         );
-                    }
-                }
+                    });
+                    let is_deck_active = session.config.get_active_deck().is_some();
 
-                // Print the current question's identifier and the question itself
-                // (without answers yet).
-                ui.heading(&print_question.question.identifier);
-                ui.label(&print_question.question.question);
+                    // Show and handle question categories (which do not apply while a
+                    // study deck is active). A change which would leave no question to
+                    // ask is reverted.
+                    ui.horizontal(|ui| {
+                        ui.set_enabled(!is_deck_active);
+                        ui.label("Fragekategorien:");
+                        let previous_config = session.config.clone();
+                        let mut has_changed = false;
+                        if ui.checkbox(&mut session.config.include_v, "V").changed() {
+                            if session.config.all_includes_false() {
+                                session.config.include_v = true;
+                            }
+                            has_changed = true;
+                        }
+                        if ui.checkbox(&mut session.config.include_b, "B").changed() {
+                            if session.config.all_includes_false() {
+                                session.config.include_b = true;
+                            }
+                            has_changed = true;
+                        }
+                        if ui.checkbox(&mut session.config.include_n, "N").changed() {
+                            if session.config.all_includes_false() {
+                                session.config.include_n = true;
+                            }
+                            has_changed = true;
+                        }
+                        if ui.checkbox(&mut session.config.include_e, "E").changed() {
+                            if session.config.all_includes_false() {
+                                session.config.include_e = true;
+                            }
+                            has_changed = true;
+                        }
+                        if ui.checkbox(&mut session.config.include_a, "A").changed() {
+                            if session.config.all_includes_false() {
+                                session.config.include_a = true;
+                            }
+                            has_changed = true;
+                        }
+                        if has_changed {
+                            if session.update_eligible_questions() {
+                                record_error(session.config.save(), &mut last_error);
+                            } else {
+                                session.config = previous_config;
+                            }
+                        }
+                    });
 
-                // Show picture answers (if they exist)
-                if !print_question.question.picture_a.is_empty() {
-                    ui.separator();
+                    // Show and handle the filter expression of the preferred questions
+                    // (see the filter module)
+                    let filter_description = session
+                        .config
+                        .filter
+                        .as_ref()
+                        .map_or(String::from("keiner"), describe_filter);
+                    egui::CollapsingHeader::new(format!("Filter: {}", filter_description))
+                        .id_source("filter")
+                        .show(ui, |ui| {
+                            show_filter_settings(ui, &mut session, &section_paths, &mut last_error);
+                        });
 
+                    // Show and handle the algorithm for choosing the next question
                     ui.horizontal(|ui| {
-                        for &index_name_tuple in &index_name_tuples {
-                            ui.label(format!("{}:", index_name_tuple.1));
-                            ui.add(
-                                egui::Image::new(format!(
-                                    "file://resources/fragenkatalog/svgs/{}.svg",
-                                    print_question.get_shuffled_picture(index_name_tuple.0)
-                                ))
-                                .fit_to_exact_size(Vec2::new(MAX_IMAGE_WIDTH, MAX_IMAGE_HEIGHT))
-                                .maintain_aspect_ratio(true)
-                                .bg_fill(Color32::DARK_GRAY),
-                            );
+                        ui.label("Lernalgorithmus:");
+                        for scheduler_mode in SchedulerMode::ALL {
+                            if ui
+                                .selectable_value(
+                                    &mut session.config.scheduler_mode,
+                                    scheduler_mode,
+                                    get_scheduler_mode_name(scheduler_mode),
+                                )
+                                .clicked()
+                            {
+                                record_error(session.config.save(), &mut last_error);
+                                session.update_statistics();
+                            }
+                        }
+                        if session.config.scheduler_mode != SchedulerMode::Legacy
+                            && ui
+                                .checkbox(
+                                    &mut session.config.only_due_today,
+                                    "Nur heute fällige Fragen",
+                                )
+                                .changed()
+                        {
+                            record_error(session.config.save(), &mut last_error);
+                        }
+                    });
+
+                    // Show and handle the selection of catalog sections ("Kapitel"), which
+                    // do not apply while a study deck is active. A change which would leave
+                    // no question to ask is reverted.
+                    egui::CollapsingHeader::new("Kapitel").show(ui, |ui| {
+                        ui.set_enabled(!is_deck_active);
+                        let previous_excluded_sections = session.config.excluded_sections.clone();
+                        let mut has_changed = false;
+                        if ui.button("Alle Kapitel auswählen").clicked() {
+                            session.config.excluded_sections.clear();
+                            has_changed = true;
+                        }
+                        for section_path in &section_paths {
+                            ui.horizontal(|ui| {
+                                ui.add_space(16.0 * (section_path.len() - 1) as f32);
+                                let mut is_included =
+                                    session.config.is_section_included(section_path);
+                                let title = section_path.last().unwrap();
+                                if ui.checkbox(&mut is_included, title).changed() {
+                                    session.config.set_section_included(
+                                        section_path,
+                                        is_included,
+                                        &section_paths,
+                                    );
+                                    has_changed = true;
+                                }
+                                if ui.small_button("Nur dieses").clicked() {
+                                    session
+                                        .config
+                                        .select_only_section(section_path, &section_paths);
+                                    has_changed = true;
+                                }
+                            });
+                        }
+                        if has_changed {
+                            if session.update_eligible_questions() {
+                                record_error(session.config.save(), &mut last_error);
+                            } else {
+                                session.config.excluded_sections = previous_excluded_sections;
+                            }
                         }
                     });
-                } else {
-                    answer_text = String::from("Antwort");
-                }
 
-                // Handle answer printing (if an answer has no text, "" is displayed)
-                for &index_name_tuple in &index_name_tuples {
+                    // Show picture question if one exists. If its file is missing, a
+                    // note is shown instead.
                     ui.separator();
-                    if ui.button(format!("{} {}", answer_text, index_name_tuple.1)).clicked() && !has_answered {
-                        has_answered = true;
-                        has_answered_first = true;
-                        given_answer = index_name_tuple.0;
+                    let print_question = session.get_print_question();
+                    if !print_question.question.picture_question.is_empty() {
+                        show_picture(
+                            ui,
+                            &picture_index,
+                            &print_question.question.picture_question,
+                        );
                     }
-                    ui.label(print_question.get_shuffled_answer(index_name_tuple.0));
-                }
 
-                // Handling of question marking
-                ui.separator();
-                if learn_states.get(&print_question.question.identifier).unwrap().marked {
-                    if ui.button("[X] Entmarkieren").clicked() {
-                        learn_states.get_mut(&print_question.question.identifier).unwrap().marked = false;
+                    // Print the current question's identifier and the question itself
+                    // (without answers yet).
+                    ui.heading(&print_question.question.identifier);
+                    show_text_with_math(ui, &print_question.question.question);
+
+                    // Show picture answers (if they exist)
+                    if !print_question.question.picture_a.is_empty() {
+                        ui.separator();
+
+                        ui.horizontal(|ui| {
+                            for &index_name_tuple in &index_name_tuples {
+                                ui.label(format!("{}:", index_name_tuple.1));
+                                show_picture(
+                                    ui,
+                                    &picture_index,
+                                    &print_question.get_shuffled_picture(index_name_tuple.0),
+                                );
+                            }
+                        });
+                    } else {
+                        answer_text = String::from("Antwort");
                     }
-                    save_learning(&learn_states);
-                } else {
-                    if ui.button("[ ] Markieren").clicked() {
-                        learn_states.get_mut(&print_question.question.identifier).unwrap().marked = true;
+
+                    // Handle answer printing (if an answer has no text, "" is displayed).
+                    // Each button can also be "clicked" with its keyboard shortcuts.
+                    let key_bindings = session.config.key_bindings.clone();
+                    for &index_name_tuple in &index_name_tuples {
+                        ui.separator();
+                        let answer_keys = key_bindings.get_answer_keys(index_name_tuple.0);
+                        let answer_button = ui.button(format!(
+                            "{} {}{}",
+                            answer_text,
+                            index_name_tuple.1,
+                            get_shortcut_hint(answer_keys)
+                        ));
+                        if (answer_button.clicked() || is_shortcut_pressed(ui, answer_keys))
+                            && session.get_given_answer().is_none()
+                        {
+                            record_error(session.answer(index_name_tuple.0), &mut last_error);
+                        }
+                        show_text_with_math(
+                            ui,
+                            &session
+                                .get_print_question()
+                                .get_shuffled_answer(index_name_tuple.0),
+                        );
                     }
-                    save_learning(&learn_states);
-                }
 
-                // Handling of the case that the user answered the question
-                ui.separator();
-                if has_answered {
-                    if print_question.answer_shuffle[given_answer] == Answer::A {
-                        ui.label("Korrekt!");
-                        if has_answered_first {
-                            correct_answers_since_start += 1;
-                            answers_since_start += 1;
-                            learning::handle_correct_answer(
-                                &mut learn_states,
-                                &print_question.question.identifier,
-                                &config,
-                            );
-                            learning::save_learning(&learn_states);
-                            statistics = Statistics::new(&eligible_questions, &learn_states);
-                            has_answered_first = false;
+                    // Handling of question marking
+                    ui.separator();
+                    let mark_hint = get_shortcut_hint(&key_bindings.mark);
+                    let mark_button = if session.is_marked() {
+                        ui.button(format!("[X] Entmarkieren{}", mark_hint))
+                    } else {
+                        ui.button(format!("[ ] Markieren{}", mark_hint))
+                    };
+                    if mark_button.clicked() || is_shortcut_pressed(ui, &key_bindings.mark) {
+                        record_error(session.set_marked(!session.is_marked()), &mut last_error);
+                    }
+                    show_tags_editor(ui, &mut session, &mut new_tag, &mut last_error);
+
+                    // Handling of the case that the user answered the question
+                    ui.separator();
+                    if let Some(is_correct) = session.is_given_answer_correct() {
+                        if is_correct {
+                            ui.label("Korrekt!");
+                        } else {
+                            ui.label(format!(
+                                "Falsch! Richtige Antwort ist {:?}",
+                                session.get_print_question().get_correct_answer()
+                            ));
+                        }
+
+                        // The user's note on the question, which is saved as soon as the
+                        // note field loses the focus (e.g. when "Nächste Frage" is clicked)
+                        ui.label("Notiz (z. B. eine Eselsbrücke):");
+                        let mut note = session.get_note().to_string();
+                        let note_field = ui.add(
+                            egui::TextEdit::multiline(&mut note)
+                                .desired_rows(2)
+                                .desired_width(f32::INFINITY),
+                        );
+                        if note_field.changed() {
+                            let identifier =
+                                session.get_print_question().question.identifier.clone();
+                            session.learn_states.entry(identifier).or_default().note = note;
+                        }
+                        if note_field.lost_focus() {
+                            record_error(session.save_learning(), &mut last_error);
+                        }
+
+                        let next_button = ui.button(format!(
+                            "Nächste Frage{}",
+                            get_shortcut_hint(&key_bindings.next)
+                        ));
+                        if next_button.clicked() || is_shortcut_pressed(ui, &key_bindings.next) {
+                            session.next_question();
                         }
                     } else {
-                        ui.label(format!(
-                            "Falsch! Richtige Antwort ist {:?}",
-                            print_question.get_correct_answer()
+                        let skip_button = ui.button(format!(
+                            "Überspringen{}",
+                            get_shortcut_hint(&key_bindings.skip)
                         ));
-                        if has_answered_first {
-                            answers_since_start += 1;
-                            learning::handle_wrong_answer(
-                                &mut learn_states,
-                                &print_question.question.identifier,
-                            );
-                            learning::save_learning(&learn_states);
-                            statistics = Statistics::new(&eligible_questions, &learn_states);
-                            has_answered_first = false;
+                        if skip_button.clicked() || is_shortcut_pressed(ui, &key_bindings.skip) {
+                            session.next_question();
                         }
                     }
-                    if ui.button("Nächste Frage").clicked() {
-                        print_question =
-                            learning::get_next_print_question(&eligible_questions, &mut learn_states, &config);
-                        has_answered = false;
+                    let correct_answers_since_start = session.get_correct_answers_since_start();
+                    let answers_since_start = session.get_answers_since_start();
+                    let statistics = session.get_statistics();
+                    ui.separator();
+                    ui.label(RichText::new("Aktuelle Session:".to_string()).strong());
+                    ui.label(format!(
+                        "Korrekt beantwortete Fragen: {}, {} %",
+                        correct_answers_since_start,
+                        (correct_answers_since_start * 100)
+                            .checked_div(answers_since_start)
+                            .unwrap_or(0)
+                    ));
+                    ui.label(format!(
+                        "Insgesamt beantwortete Fragen: {}",
+                        answers_since_start
+                    ));
+                    ui.separator();
+                    ui.label(RichText::new("Lernfortschritt:".to_string()).strong());
+                    ui.label(format!(
+                        "Korrekt beantwortete Fragen: {}, {} %",
+                        statistics.correct_answers,
+                        (statistics.correct_answers * 100)
+                            .checked_div(statistics.questions)
+                            .unwrap_or(0)
+                    ));
+                    ui.label(format!(
+                        "Noch nicht korrekt beantwortete Fragen: {}, {} %",
+                        statistics.no_correct_answers,
+                        (statistics.no_correct_answers * 100)
+                            .checked_div(statistics.questions)
+                            .unwrap_or(0)
+                    ));
+                    ui.label(format!("Fragen insgesamt: {}", statistics.questions));
+                    ui.label(format!("Heute fällige Fragen: {}", statistics.due_today));
+                    ui.separator();
+                    let mut keys = statistics.count_per_bin.keys().collect::<Vec<_>>();
+                    keys.sort();
+                    ui.label(RichText::new("Fragen pro Lerntopf:".to_string()).strong());
+                    for i in keys.iter() {
+                        let count = &statistics.count_per_bin.get(i).unwrap();
+                        ui.label(format!("Lerntopf '{}': {}", i, count));
                     }
-                } else if ui.button("Überspringen").clicked() {
-                    print_question =
-                        learning::get_next_print_question(&eligible_questions, &mut learn_states, &config);
-                }
-                ui.separator();
-                ui.label(RichText::new("Aktuelle Session:".to_string()).strong());
-                ui.label(format!("Korrekt beantwortete Fragen: {}, {} %", correct_answers_since_start, if answers_since_start > 0 {correct_answers_since_start * 100 / answers_since_start} else {0}));
-                ui.label(format!("Insgesamt beantwortete Fragen: {}", answers_since_start));
-                ui.separator();
-                ui.label(RichText::new("Lernfortschritt:".to_string()).strong());
-                ui.label(format!("Korrekt beantwortete Fragen: {}, {} %", statistics.correct_answers, if statistics.questions > 0 {statistics.correct_answers * 100 / statistics.questions} else {0}));
-                ui.label(format!("Noch nicht korrekt beantwortete Fragen: {}, {} %", statistics.no_correct_answers, if statistics.questions > 0 {statistics.no_correct_answers * 100 / statistics.questions} else {0}));
-                ui.label(format!("Fragen insgesamt: {}", statistics.questions));
-                ui.separator();
-                let mut keys = statistics.count_per_bin.keys().collect::<Vec<_>>();
-                keys.sort();
-                ui.label(RichText::new("Fragen pro Lerntopf:".to_string()).strong());
-                for i in keys.iter() {
-                    let count = &statistics.count_per_bin.get(i).unwrap();
-                    ui.label(format!("Lerntopf '{}': {}", i, count));
-                }
+                });
             });
         });
-    })
+    result.map(|_| gui_exit.get())
 }
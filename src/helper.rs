@@ -1,11 +1,13 @@
 //! This module contains small but possibly useful
 //! Rust utility functions, currently focused on
-//! file I/O and time.
+//! file I/O and time. All file I/O functions return
+//! an FfhError::Io (see the error module) on failure.
 
 // MACRO DIRECTIVES SECTION //
 #![allow(dead_code)]
 
 // IMPORTS SECTION //
+use crate::error::{FfhError, Result};
 use rayon::prelude::*;
 use std::fs;
 use std::io::Write;
@@ -43,47 +45,62 @@ pub fn get_current_unixtime_in_sec() -> u64 {
 
 /// Returns the filenames in the given directory
 ///
-/// You must make sure beforehand that the directory exists. Otherwise,
-/// the function panics.
-pub fn get_filenames_in_dir(dir: &str) -> Vec<String> {
+/// ### Return value
+/// * The paths of all files (not subdirectories) in the directory, or an
+///   FfhError::Io if the directory cannot be read
+pub fn get_filenames_in_dir(dir: &str) -> Result<Vec<String>> {
     let path = Path::new(dir);
-    let filenames: Vec<String> = fs::read_dir(path)
-        .unwrap()
-        .par_bridge()
-        .map(|path| path.unwrap().path())
+    let entries: Vec<fs::DirEntry> = fs::read_dir(path)
+        .and_then(|entries| entries.collect())
+        .map_err(|e| FfhError::io(dir, e))?;
+    let filenames: Vec<String> = entries
+        .par_iter()
+        .map(|entry| entry.path())
         .filter(|path| path.is_file())
-        .map(|path| path.to_str().unwrap().to_owned().replace('\\', "/"))
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
         .collect();
-    filenames
+    Ok(filenames)
 }
 
-/// Returns all lines of the given file as Vec<String>.
-///
-/// Make sure that the file exists beforehand. Otherwise,
-/// this function panics.
-pub fn read_filelines(filename: &str) -> Vec<String> {
-    fs::read_to_string(filename)
-        .unwrap()
-        .par_lines()
-        .map(String::from)
-        .collect()
+/// Returns all lines of the given file as Vec<String>, or an FfhError::Io
+/// if the file cannot be read.
+pub fn read_filelines(filename: &str) -> Result<Vec<String>> {
+    let text = fs::read_to_string(filename).map_err(|e| FfhError::io(filename, e))?;
+    Ok(text.par_lines().map(String::from).collect())
 }
 
-pub fn overwrite_file_lines(filepath: &str, lines: Vec<String>) {
+pub fn overwrite_file_lines(filepath: &str, lines: Vec<String>) -> Result<()> {
     let path = Path::new(filepath);
-    let mut f = fs::File::create(path).unwrap();
-    for line in lines {
-        writeln!(f, "{}", line).unwrap();
-    }
+    let write_lines = || -> std::io::Result<()> {
+        let mut f = fs::File::create(path)?;
+        for line in lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    };
+    write_lines().map_err(|e| FfhError::io(filepath, e))
+}
+
+pub fn overwrite_file_str(filepath: &str, text: &str) -> Result<()> {
+    let path = Path::new(filepath);
+    fs::File::create(path)
+        .and_then(|mut f| writeln!(f, "{}", text))
+        .map_err(|e| FfhError::io(filepath, e))
 }
 
-pub fn overwrite_file_str(filepath: &str, text: &str) {
+/// Appends the given line to the given file. If the file does
+/// not exist, it is created.
+pub fn append_file_line(filepath: &str, line: &str) -> Result<()> {
     let path = Path::new(filepath);
-    let mut f = fs::File::create(path).unwrap();
-    writeln!(f, "{}", text).unwrap();
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut f| writeln!(f, "{}", line))
+        .map_err(|e| FfhError::io(filepath, e))
 }
 
-pub fn read_filetext(filename: &str) -> String {
-    let filelines = read_filelines(filename);
-    filelines.join(" ")
+pub fn read_filetext(filename: &str) -> Result<String> {
+    let filelines = read_filelines(filename)?;
+    Ok(filelines.join(" "))
 }
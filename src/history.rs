@@ -0,0 +1,380 @@
+//! This module contains the answer history of Funkfragenhelfer.
+//!
+//! While the LearnStates only contain aggregated counters per question,
+//! the answer history records every single answer as an "AnswerEvent".
+//! The events are appended to the file "history.jsonl" next to the
+//! "learning.json" (one JSON object per line), which is never rewritten. Hence, the history
+//! allows to look at the learning progress over time and to rebuild the
+//! LearnStates if the "learning.json" file got lost or corrupted.
+//!
+//! The history only contains answers, but not the user's marks, notes and tags of
+//! the questions. When the LearnStates are rebuilt, these are taken over from the
+//! newest readable backup of the "learning.json" (see the persistence module); If
+//! there is none, they are lost, which the user is told.
+
+// IMPORTS SECTION //
+use crate::config::Config;
+use crate::error::{FfhError, Result};
+use crate::helper;
+use crate::learning::{self, Answer, LearnStates, PrintQuestion};
+use crate::persistence::Loaded;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// ENUM SECTION //
+/// The mode in which a question was answered.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub enum AnswerMode {
+    /// The normal practice mode
+    Practice,
+    /// The exam simulation (the answers are only recorded if the exam
+    /// result was applied to the learning progress)
+    Exam,
+}
+
+// STRUCT SECTION //
+/// A single answer to a question.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AnswerEvent {
+    /// The answered question's identifier
+    pub identifier: String,
+    /// The chosen answer as it was shown to the user, i.e. with
+    /// shuffled answers
+    pub shuffled_answer: Answer,
+    /// The chosen answer as in the original catalog (where A is
+    /// always correct)
+    pub original_answer: Answer,
+    /// Whether or not the answer was correct
+    pub is_correct: bool,
+    /// The UNIX time of the answer
+    pub unixtime: u64,
+    /// The time between showing the question and answering it in
+    /// milliseconds, or None if unknown (e.g. in an exam)
+    pub response_time_in_ms: Option<u64>,
+    /// The mode in which the question was answered
+    pub mode: AnswerMode,
+}
+
+impl AnswerEvent {
+    /// Creates a new AnswerEvent for the current time.
+    ///
+    /// ### Arguments
+    /// * print_question: The answered question with its shuffled answers
+    /// * given_index: The index of the chosen (shuffled) answer
+    /// * response_time_in_ms: The time needed for the answer, if known
+    /// * mode: The mode in which the question was answered
+    pub fn new(
+        print_question: &PrintQuestion,
+        given_index: usize,
+        response_time_in_ms: Option<u64>,
+        mode: AnswerMode,
+    ) -> AnswerEvent {
+        let original_answer = print_question.answer_shuffle[given_index];
+        AnswerEvent {
+            identifier: print_question.question.identifier.clone(),
+            shuffled_answer: Answer::from_index(given_index),
+            original_answer,
+            is_correct: original_answer == Answer::A,
+            unixtime: helper::get_current_unixtime_in_sec(),
+            response_time_in_ms,
+            mode,
+        }
+    }
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Appends the given event to the answer history file of the given Config.
+///
+/// That the file's directory exists is ensured through the learning
+/// module's load_learning() beforehand.
+pub fn append_event(event: &AnswerEvent, config: &Config) -> Result<()> {
+    let history_filepath = config.get_history_filepath();
+    let event_json =
+        serde_json::to_string(event).map_err(|e| FfhError::parse(&history_filepath, e))?;
+    helper::append_file_line(&history_filepath, &event_json)
+}
+
+/// Loads all events of the answer history file of the given Config in the order
+/// of their recording.
+///
+/// If the file does not exist (yet), no events are returned. Lines which cannot be
+/// read (e.g. a line which was only partially written because of a crash) are
+/// skipped, so that the rest of the history stays usable.
+///
+/// ### Return value
+/// * The events together with a warning for the user which lists the skipped
+///   lines (if any), or an FfhError::Io if the file exists but cannot be read
+pub fn load_events(config: &Config) -> Result<Loaded<Vec<AnswerEvent>>> {
+    let history_filepath = config.get_history_filepath();
+    if !Path::new(&history_filepath).exists() {
+        return Ok(Loaded {
+            value: Vec::new(),
+            warning: None,
+        });
+    }
+    let mut events = Vec::new();
+    let mut skipped_line_numbers = Vec::new();
+    for (index, line) in helper::read_filelines(&history_filepath)?
+        .iter()
+        .enumerate()
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(event) => events.push(event),
+            Err(_) => skipped_line_numbers.push((index + 1).to_string()),
+        }
+    }
+    let warning = (!skipped_line_numbers.is_empty()).then(|| {
+        format!(
+            "Die Zeilen {} der Antworthistorie '{}' waren unlesbar und wurden übersprungen.",
+            skipped_line_numbers.join(", "),
+            history_filepath
+        )
+    });
+    Ok(Loaded {
+        value: events,
+        warning,
+    })
+}
+
+/// Rebuilds the LearnStates by replaying the given events in chronological order,
+/// as if each question had been answered again at the time of its event.
+///
+/// As the user's marks, notes and tags are not part of the answer history, they
+/// are taken over from the given previous LearnStates (e.g. of a backup), also
+/// for questions which were never answered.
+///
+/// ### Arguments
+/// * events: The answer events, e.g. as loaded by load_events()
+/// * config: The current Funkfragenhelfer Config; Determines the maximal bin.
+/// * previous_learn_states: The LearnStates whose marks, notes and tags are kept
+///
+/// ### Return value
+/// * The rebuilt LearnStates, containing an entry for each answered question and
+///   for each question with a mark, note or tag
+pub fn rebuild_learn_states(
+    events: &[AnswerEvent],
+    config: &Config,
+    previous_learn_states: &LearnStates,
+) -> LearnStates {
+    let mut sorted_events: Vec<&AnswerEvent> = events.iter().collect();
+    sorted_events.sort_by_key(|event| event.unixtime);
+
+    let mut learn_states = LearnStates::new();
+    for event in sorted_events {
+        if event.is_correct {
+            learning::handle_correct_answer_at(
+                &mut learn_states,
+                &event.identifier,
+                config,
+                event.unixtime,
+            );
+        } else {
+            learning::handle_wrong_answer_at(
+                &mut learn_states,
+                &event.identifier,
+                event.original_answer,
+                event.unixtime,
+            );
+        }
+    }
+    for (identifier, previous_learn_state) in previous_learn_states {
+        let has_user_data = previous_learn_state.marked
+            || !previous_learn_state.note.is_empty()
+            || !previous_learn_state.tags.is_empty();
+        if !has_user_data {
+            continue;
+        }
+        let learn_state = learn_states.entry(identifier.clone()).or_default();
+        learn_state.marked = previous_learn_state.marked;
+        learn_state.note = previous_learn_state.note.clone();
+        learn_state.tags = previous_learn_state.tags.clone();
+    }
+    learn_states
+}
+
+/// Rebuilds the learning.json of the given Config from the answer history (see
+/// rebuild_learn_states()), e.g. if the learning.json cannot be read or repaired.
+/// The marks, notes and tags are taken over from the newest readable backup of
+/// the learning.json. The original file is kept as "<file>.corrupt".
+///
+/// ### Return value
+/// * A message for the user which says what was recovered and what was lost, or
+///   an FfhError if the history could not be read or the learning.json not saved
+pub fn rebuild_learning(config: &Config) -> Result<String> {
+    let loaded = load_events(config)?;
+    let backup = learning::read_newest_learning_backup(config);
+    let no_learn_states = LearnStates::new();
+    let previous_learn_states = backup
+        .as_ref()
+        .map_or(&no_learn_states, |(learn_states, _)| learn_states);
+    let learn_states = rebuild_learn_states(&loaded.value, config, previous_learn_states);
+    learning::reset_learning(config)?;
+    learning::save_learning(&learn_states, config)?;
+
+    let mut message = format!(
+        "Der Lernfortschritt wurde aus {} Antworten der Antworthistorie wiederhergestellt.",
+        loaded.value.len()
+    );
+    match &backup {
+        Some((_, backup_filepath)) => message.push_str(&format!(
+            " Markierungen, Notizen und Tags wurden aus der Sicherung '{}' übernommen.",
+            backup_filepath
+        )),
+        None => message.push_str(
+            " Da keine lesbare Sicherung existiert, gingen Markierungen, Notizen und Tags verloren.",
+        ),
+    }
+    if let Some(warning) = loaded.warning {
+        message.push(' ');
+        message.push_str(&warning);
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Returns a Config whose data dir is a new, empty temporary directory
+    /// with the given name.
+    fn get_temp_config(name: &str) -> Config {
+        let data_dir =
+            std::env::temp_dir().join(format!("ffh-history-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&data_dir);
+        let mut config = Config::new();
+        config.data_dir = data_dir.to_string_lossy().to_string();
+        helper::ensure_dir_existence(
+            &Path::new(&config.get_learning_filepath())
+                .parent()
+                .unwrap()
+                .to_string_lossy(),
+        );
+        config
+    }
+
+    /// Returns an answer event of the given question at the given time.
+    fn get_event(identifier: &str, is_correct: bool, unixtime: u64) -> AnswerEvent {
+        let answer = if is_correct { Answer::A } else { Answer::C };
+        AnswerEvent {
+            identifier: identifier.to_string(),
+            shuffled_answer: answer,
+            original_answer: answer,
+            is_correct,
+            unixtime,
+            response_time_in_ms: None,
+            mode: AnswerMode::Practice,
+        }
+    }
+
+    #[test]
+    fn unreadable_history_lines_are_skipped_with_a_warning() {
+        let config = get_temp_config("skip");
+        append_event(&get_event("NA101", true, 1), &config).unwrap();
+        helper::append_file_line(&config.get_history_filepath(), "{\"identifier\": \"NA1").unwrap();
+        append_event(&get_event("NA102", false, 2), &config).unwrap();
+
+        let loaded = load_events(&config).unwrap();
+        fs::remove_dir_all(&config.data_dir).unwrap();
+        assert_eq!(loaded.value.len(), 2);
+        assert!(loaded.warning.unwrap().contains("Zeilen 2 "));
+    }
+
+    #[test]
+    fn missing_history_has_no_events() {
+        let config = get_temp_config("missing");
+        let loaded = load_events(&config).unwrap();
+        fs::remove_dir_all(&config.data_dir).unwrap();
+        assert!(loaded.value.is_empty());
+        assert!(loaded.warning.is_none());
+    }
+
+    #[test]
+    fn rebuild_replays_the_events_chronologically() {
+        let config = Config::new();
+        // Recorded out of order, e.g. by an applied exam
+        let events = [
+            get_event("NA101", true, 30),
+            get_event("NA101", false, 10),
+            get_event("NA101", true, 20),
+        ];
+
+        let learn_states = rebuild_learn_states(&events, &config, &LearnStates::new());
+        let learn_state = &learn_states["NA101"];
+        assert_eq!(learn_state.current_bin, 3);
+        assert_eq!(learn_state.correct, 2);
+        assert_eq!(learn_state.wrong, 1);
+        assert_eq!(learn_state.rounds_since_wrong, 2);
+        assert_eq!(learn_state.time_last_answer, 30);
+        assert_eq!(learn_state.wrong_answer_counts[&Answer::C], 1);
+    }
+
+    #[test]
+    fn rebuild_keeps_marks_notes_and_tags() {
+        let config = Config::new();
+        let mut previous_learn_states = LearnStates::new();
+        let answered = previous_learn_states
+            .entry(String::from("NA101"))
+            .or_default();
+        answered.marked = true;
+        answered.current_bin = 5;
+        let unanswered = previous_learn_states
+            .entry(String::from("NA102"))
+            .or_default();
+        unanswered.note = String::from("Ohmsches Gesetz");
+        unanswered.tags = vec![String::from("Formel")];
+        previous_learn_states.insert(String::from("NA103"), learning::LearnState::new());
+
+        let learn_states = rebuild_learn_states(
+            &[get_event("NA101", true, 1)],
+            &config,
+            &previous_learn_states,
+        );
+        assert_eq!(learn_states.len(), 2);
+        assert!(learn_states["NA101"].marked);
+        assert_eq!(learn_states["NA101"].current_bin, 1);
+        assert_eq!(learn_states["NA102"].note, "Ohmsches Gesetz");
+        assert_eq!(learn_states["NA102"].tags, ["Formel"]);
+        assert_eq!(learn_states["NA102"].correct, 0);
+    }
+
+    #[test]
+    fn corrupted_learning_is_rebuilt_with_the_backup_user_data() {
+        let config = get_temp_config("rebuild");
+        let mut learn_states = LearnStates::new();
+        learn_states.entry(String::from("NA102")).or_default().note = String::from("Notiz");
+        learning::save_learning(&learn_states, &config).unwrap();
+        // Loading makes the backup
+        learning::load_learning(&config).unwrap();
+        fs::write(config.get_learning_filepath(), "kaputt").unwrap();
+        append_event(&get_event("NA101", true, 1), &config).unwrap();
+        append_event(&get_event("NA101", true, 2), &config).unwrap();
+
+        let message = rebuild_learning(&config).unwrap();
+        let rebuilt_learn_states = learning::load_learning(&config).unwrap().value;
+        let has_corrupt_file =
+            Path::new(&format!("{}.corrupt", config.get_learning_filepath())).exists();
+        fs::remove_dir_all(&config.data_dir).unwrap();
+        assert!(message.contains("aus 2 Antworten"));
+        assert!(message.contains("übernommen"));
+        assert_eq!(rebuilt_learn_states["NA101"].current_bin, 2);
+        assert_eq!(rebuilt_learn_states["NA102"].note, "Notiz");
+        assert!(has_corrupt_file);
+    }
+
+    #[test]
+    fn rebuild_without_backup_reports_the_lost_user_data() {
+        let config = get_temp_config("nobackup");
+        fs::write(config.get_learning_filepath(), "kaputt").unwrap();
+        append_event(&get_event("NA101", false, 1), &config).unwrap();
+
+        let message = rebuild_learning(&config).unwrap();
+        let rebuilt_learn_states = learning::load_learning(&config).unwrap().value;
+        fs::remove_dir_all(&config.data_dir).unwrap();
+        assert!(message.contains("verloren"));
+        assert_eq!(rebuilt_learn_states["NA101"].current_bin, 1);
+    }
+}
@@ -4,18 +4,23 @@
 
 // IMPORTS SECTION //
 use crate::config::Config;
+use crate::error::Result;
+use crate::migration::{LEARNING_SCHEMA, LEARNING_VERSION};
+use crate::persistence::{self, Loaded};
 use crate::question::Question;
+use crate::scheduler::{self, FsrsState, Sm2State};
 use crate::{config, helper};
 use rand::seq::SliceRandom;
-use rand::{self, Rng};
+use rand::{Rng, RngCore};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::borrow::BorrowMut;
 use std::collections::HashMap;
 use std::path::Path;
 
 // ENUMS SECTION //
 /// Represents the possible answers A to B.
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
 pub enum Answer {
     A,
     B,
@@ -23,6 +28,20 @@ pub enum Answer {
     D,
 }
 
+impl Answer {
+    /// Returns the answer at the given (0-based) position, i.e. A for 0 up to D for 3.
+    /// Panics if the index is greater than 3.
+    pub fn from_index(index: usize) -> Answer {
+        match index {
+            0 => Answer::A,
+            1 => Answer::B,
+            2 => Answer::C,
+            3 => Answer::D,
+            _ => panic!("Invalid answer index {}", index),
+        }
+    }
+}
+
 // STRUCTS SECTION //
 /// Represents the learning progress or "state" of a question.
 ///
@@ -49,6 +68,26 @@ pub struct LearnState {
     pub rounds_since_wrong: u64,
     /// The UNIX time when this question was last answered
     pub time_last_answer: u64,
+    /// Number of times each wrong answer (as in the original catalog,
+    /// i.e. B, C or D) was chosen
+    #[serde(default)]
+    pub wrong_answer_counts: HashMap<Answer, u64>,
+    /// The state of the SM-2 scheduler (see the scheduler module)
+    #[serde(default)]
+    pub sm2: Sm2State,
+    /// The state of the FSRS scheduler (see the scheduler module)
+    #[serde(default)]
+    pub fsrs: FsrsState,
+    /// The user's personal note on the question (e.g. a mnemonic), or an
+    /// empty string if there is none. As Funkfragenhelfer has no export, the
+    /// notes are only kept here (and thus in the learning.json's backups, from
+    /// which rebuild_learning() of the history module takes them over).
+    #[serde(default)]
+    pub note: String,
+    /// The user's tags of the question (e.g. "Formel"), which can define
+    /// study decks (see the deck module)
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl LearnState {
@@ -62,10 +101,21 @@ impl LearnState {
             marked: false,
             rounds_since_wrong: 0,
             time_last_answer: 0,
+            wrong_answer_counts: HashMap::new(),
+            sm2: Sm2State::default(),
+            fsrs: FsrsState::default(),
+            note: String::new(),
+            tags: Vec::new(),
         }
     }
 }
 
+impl Default for LearnState {
+    fn default() -> Self {
+        LearnState::new()
+    }
+}
+
 /// Statistic information about the learning state.
 pub struct Statistics {
     /// Number of questions currently learning
@@ -76,11 +126,40 @@ pub struct Statistics {
     pub no_correct_answers: usize,
     /// Number of questions per learning "bin"
     pub count_per_bin: HashMap<u64, usize>,
+    /// Number of already answered questions which are due until the end of
+    /// the current (UTC) day according to the configured scheduler
+    pub due_today: usize,
+}
+
+/// A wrong answer which was chosen repeatedly for the same question,
+/// hinting at a specific misconception.
+pub struct Confusion {
+    /// The question
+    pub question: Question,
+    /// The repeatedly chosen wrong answer as in the original catalog
+    pub wrong_answer: Answer,
+    /// Number of times the wrong answer was chosen
+    pub count: u64,
 }
 
 /// Type alias for the full collection of LearnState instances.
 pub type LearnStates = HashMap<String, LearnState>;
 
+/// The content of the learning.json, i.e. the LearnStates together with
+/// the schema version (see the migration module).
+#[derive(Serialize, Deserialize)]
+struct LearningFile {
+    version: u64,
+    learn_states: LearnStates,
+}
+
+/// The content of the learning.json for saving, which only borrows the LearnStates.
+#[derive(Serialize)]
+struct LearningFileRef<'a> {
+    version: u64,
+    learn_states: &'a LearnStates,
+}
+
 /// Representation of a print-friendly question.
 ///
 /// In contrast to a normal Question, which is included here too,
@@ -93,6 +172,17 @@ pub struct PrintQuestion {
 }
 
 impl PrintQuestion {
+    /// Creates a new PrintQuestion for the given question with answers which are
+    /// randomly shuffled with the given random number generator.
+    pub fn new(question: Question, rng: &mut dyn RngCore) -> PrintQuestion {
+        let mut answer_shuffle = vec![Answer::A, Answer::B, Answer::C, Answer::D];
+        answer_shuffle.shuffle(rng);
+        PrintQuestion {
+            question,
+            answer_shuffle,
+        }
+    }
+
     /// Looks where the "real" answer A is in the randomly shuffled answers
     /// and returns its current Answer value. A is looked up as in
     /// the questions database of the Bundesnetzagentur, A is always
@@ -111,27 +201,38 @@ impl PrintQuestion {
 
     /// Returns the question string for the given randomly shuffled answer.
     pub fn get_shuffled_answer(&self, index: usize) -> String {
-        match self.answer_shuffle[index] {
-            Answer::A => String::from(&self.question.answer_a),
-            Answer::B => String::from(&self.question.answer_b),
-            Answer::C => String::from(&self.question.answer_c),
-            Answer::D => String::from(&self.question.answer_d),
-        }
+        get_answer_text(&self.question, self.answer_shuffle[index])
     }
 
     pub fn get_shuffled_picture(&self, index: usize) -> String {
-        match self.answer_shuffle[index] {
-            Answer::A => String::from(&self.question.picture_a),
-            Answer::B => String::from(&self.question.picture_b),
-            Answer::C => String::from(&self.question.picture_c),
-            Answer::D => String::from(&self.question.picture_d),
-        }
+        get_answer_picture(&self.question, self.answer_shuffle[index])
     }
 }
 
 // PUBLIC FUNCTIONS SECTION //
+/// Returns the text of the given answer (as in the original catalog) of the given question.
+pub fn get_answer_text(question: &Question, answer: Answer) -> String {
+    match answer {
+        Answer::A => String::from(&question.answer_a),
+        Answer::B => String::from(&question.answer_b),
+        Answer::C => String::from(&question.answer_c),
+        Answer::D => String::from(&question.answer_d),
+    }
+}
+
+/// Returns the picture identifier of the given answer (as in the original catalog) of
+/// the given question, or an empty string if the answer has no picture.
+pub fn get_answer_picture(question: &Question, answer: Answer) -> String {
+    match answer {
+        Answer::A => String::from(&question.picture_a),
+        Answer::B => String::from(&question.picture_b),
+        Answer::C => String::from(&question.picture_c),
+        Answer::D => String::from(&question.picture_d),
+    }
+}
+
 /// Updates the LearnState of the given question for the case that
-/// it was answered correctly.
+/// it was answered correctly (now).
 ///
 /// ### Arguments
 /// * learning: All current LearnStates
@@ -143,12 +244,34 @@ pub fn handle_correct_answer(
     learning: &mut LearnStates,
     identifier: &str,
     config: &config::Config,
+) {
+    let now = helper::get_current_unixtime_in_sec();
+    handle_correct_answer_at(learning, identifier, config, now);
+}
+
+/// Updates the LearnState of the given question for the case that
+/// it was answered correctly at the given time (e.g. when replaying
+/// the answer history).
+///
+/// ### Arguments
+/// * learning: All current LearnStates
+/// * identifier: The correctly answered question's identifier (i.e., th
+///   LearnStates key).
+/// * config: The current Funkfragenhelfer configurarion; Determined the
+///   maximal bin.
+/// * unixtime: The UNIX time of the answer
+pub fn handle_correct_answer_at(
+    learning: &mut LearnStates,
+    identifier: &str,
+    config: &config::Config,
+    unixtime: u64,
 ) {
     // Get the LearnState of the question (or create one
     // if it doesn't exist yet)
-    let learn_state = learning
-        .entry(identifier.to_string())
-        .or_insert(LearnState::new());
+    let learn_state = learning.entry(identifier.to_string()).or_default();
+    // Update the scheduler-specific fields (before the time of the
+    // last answer is overwritten)
+    scheduler::update_learn_state(learn_state, true, unixtime);
     // Set the bin 1 higher (or keep it at the maximum)
     if learn_state.current_bin < config.max_learn_bin {
         learn_state.current_bin += 1;
@@ -156,168 +279,270 @@ pub fn handle_correct_answer(
     // Update the rest of the LearnState statistics
     learn_state.correct += 1;
     learn_state.rounds_since_wrong += 1;
-    learn_state.time_last_answer = helper::get_current_unixtime_in_sec();
+    learn_state.time_last_answer = unixtime;
 }
 
 /// Updates the LearnState of the given question for the case that
-/// it was answered wrongly.
+/// it was answered wrongly (now).
 ///
 /// ### Arguments
 /// * learning: All current LearnStates
 /// * identifier: The correctly answered question's identifier (i.e., th
 ///   LearnStates key).
-pub fn handle_wrong_answer(learning: &mut LearnStates, identifier: &str) {
+/// * given_answer: The chosen wrong answer as in the original catalog
+pub fn handle_wrong_answer(learning: &mut LearnStates, identifier: &str, given_answer: Answer) {
+    let now = helper::get_current_unixtime_in_sec();
+    handle_wrong_answer_at(learning, identifier, given_answer, now);
+}
+
+/// Updates the LearnState of the given question for the case that
+/// it was answered wrongly at the given time (e.g. when replaying
+/// the answer history).
+///
+/// ### Arguments
+/// * learning: All current LearnStates
+/// * identifier: The wrongly answered question's identifier (i.e., th
+///   LearnStates key).
+/// * given_answer: The chosen wrong answer as in the original catalog
+/// * unixtime: The UNIX time of the answer
+pub fn handle_wrong_answer_at(
+    learning: &mut LearnStates,
+    identifier: &str,
+    given_answer: Answer,
+    unixtime: u64,
+) {
     // Get the LearnState of the question (or create one
     // if it doesn't exist yet)
-    let learn_state = learning
-        .entry(identifier.to_string())
-        .or_insert(LearnState::new());
+    let learn_state = learning.entry(identifier.to_string()).or_default();
+    // Update the scheduler-specific fields (before the time of the
+    // last answer is overwritten)
+    scheduler::update_learn_state(learn_state, false, unixtime);
     // Set the bin to 1
     learn_state.current_bin = 1;
     // Update the rest of the LearnState statistics
     learn_state.wrong += 1;
     learn_state.rounds_since_wrong = 0;
-    learn_state.time_last_answer = helper::get_current_unixtime_in_sec();
+    learn_state.time_last_answer = unixtime;
+    *learn_state
+        .wrong_answer_counts
+        .entry(given_answer)
+        .or_insert(0) += 1;
 }
 
-/// Loads the ./learninglearning.json file, or creates one if it doesn't exist.
+/// Returns the commonly confused answers of the given questions, i.e. each wrong
+/// answer which was chosen at least the given number of times for the same question.
 ///
-/// This file contains a LearnStates JSON representation and represents the full information
-/// of each question's current learning progress.
+/// ### Arguments
+/// * questions: The questions whose confusions are looked up
+/// * learning: The current LearnStates
+/// * min_count: The minimal number of times a wrong answer must have been chosen
 ///
 /// ### Return value
-/// * The LearnStates, i.e. HashMap<String, LearnState>, of each question (with the
-///   question identifiers as keys and the associated LearnState instances as values)
-pub fn load_learning() -> LearnStates {
-    let config_dir_path = Path::new("./learning");
-    if !config_dir_path.exists() {
-        helper::ensure_dir_existence("./learning");
+/// * The confusions, with the most often chosen wrong answers first
+pub fn get_confusions(
+    questions: &[Question],
+    learning: &LearnStates,
+    min_count: u64,
+) -> Vec<Confusion> {
+    let mut confusions = Vec::new();
+    for question in questions {
+        let Some(learn_state) = learning.get(&question.identifier) else {
+            continue;
+        };
+        for wrong_answer in [Answer::B, Answer::C, Answer::D] {
+            let count = learn_state
+                .wrong_answer_counts
+                .get(&wrong_answer)
+                .copied()
+                .unwrap_or(0);
+            if count >= min_count {
+                confusions.push(Confusion {
+                    question: question.clone(),
+                    wrong_answer,
+                    count,
+                });
+            }
+        }
     }
+    confusions.sort_by_key(|confusion| std::cmp::Reverse(confusion.count));
+    confusions
+}
 
-    let config_file_path = Path::new("./learning/learning.json");
-    if !config_file_path.exists() {
-        let default_learning: LearnStates = HashMap::new();
-        let learning_json = serde_json::to_string_pretty(&default_learning).unwrap();
-        helper::overwrite_file_str("./learning/learning.json", &learning_json);
-    }
+/// Returns all tags which are used in the given LearnStates, in alphabetical order.
+pub fn get_all_tags(learning: &LearnStates) -> Vec<String> {
+    let mut tags: Vec<String> = learning
+        .values()
+        .flat_map(|learn_state| learn_state.tags.iter().cloned())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
 
-    let learning_text = helper::read_filetext("./learning/learning.json");
-    serde_json::from_str(&learning_text).unwrap()
+/// Loads the learning.json file at the Config's learning filepath, or creates one
+/// if it doesn't exist.
+///
+/// This file contains a LearnStates JSON representation and represents the full information
+/// of each question's current learning progress. An older learning.json is upgraded to the
+/// current schema version (see the migration module). If the file is corrupted, it is
+/// restored from its newest readable backup (see the persistence module).
+///
+/// ### Return value
+/// * The LearnStates, i.e. HashMap<String, LearnState>, of each question (with the
+///   question identifiers as keys and the associated LearnState instances as values),
+///   together with a warning for the user if the file had to be restored, or an
+///   FfhError if it could not be restored (see repair_learning() and reset_learning())
+pub fn load_learning(config: &Config) -> Result<Loaded<LearnStates>> {
+    let learning_filepath = ensure_learning_dir(config);
+    let loaded: Loaded<LearningFile> = persistence::load_json(
+        &learning_filepath,
+        &LEARNING_SCHEMA,
+        get_default_learning_file,
+    )?;
+    Ok(Loaded {
+        value: loaded.value.learn_states,
+        warning: loaded.warning,
+    })
 }
 
-/// Saves the current LearnStates into the ./learning/learning.json
+/// Saves the current LearnStates into the learning.json at the Config's learning filepath.
 ///
-/// The JSON file is going to be overwritten in the process. That the "learning" dir
-/// exists is ensured through this module's load_learning() beforehand.
+/// The JSON file is replaced atomically (see the persistence module), so that a crash
+/// during saving cannot corrupt it. That the file's directory exists is ensured through
+/// this module's load_learning() beforehand.
 ///
 /// ### Arguments
 /// * learn_states: The current LearnStates for each question
-pub fn save_learning(learn_states: &LearnStates) {
-    let json_string = serde_json::to_string_pretty(learn_states).unwrap();
-    helper::overwrite_file_str("./learning/learning.json", &json_string);
+/// * config: The current Funkfragenhelfer Config
+pub fn save_learning(learn_states: &LearnStates, config: &Config) -> Result<()> {
+    let learning_file = LearningFileRef {
+        version: LEARNING_VERSION,
+        learn_states,
+    };
+    persistence::save_json(&config.get_learning_filepath(), &learning_file)
+}
+
+/// Repairs the learning.json at the Config's learning filepath. Missing or unreadable
+/// fields of a LearnState are set to their default values, and LearnStates which are
+/// not readable at all are dropped (see the persistence module).
+pub fn repair_learning(config: &Config) -> Result<()> {
+    let learning_filepath = ensure_learning_dir(config);
+    persistence::repair_json(&learning_filepath, &LEARNING_SCHEMA, |value| {
+        let mut learn_states = LearnStates::new();
+        if let Some(Value::Object(entries)) = value.get("learn_states") {
+            for (identifier, entry) in entries {
+                if let Some(learn_state) =
+                    persistence::repair_struct(&LearnState::new(), entry.clone())
+                {
+                    learn_states.insert(identifier.clone(), learn_state);
+                }
+            }
+        }
+        Some(LearningFile {
+            version: LEARNING_VERSION,
+            learn_states,
+        })
+    })
+    .map(|_| ())
+}
+
+/// Reads the newest readable backup of the learning.json at the Config's learning
+/// filepath (see the persistence module), e.g. to recover the user's marks, notes
+/// and tags when the LearnStates are rebuilt from the answer history.
+///
+/// ### Return value
+/// * The backup's LearnStates together with the backup's path, or None if no
+///   backup is readable
+pub fn read_newest_learning_backup(config: &Config) -> Option<(LearnStates, String)> {
+    let (learning_file, backup_filepath) = persistence::read_newest_backup::<LearningFile>(
+        &config.get_learning_filepath(),
+        &LEARNING_SCHEMA,
+    )?;
+    Some((learning_file.learn_states, backup_filepath))
+}
+
+/// Resets the learning.json at the Config's learning filepath, i.e. the whole
+/// learning progress. Note that the answer history is kept, so that the learning
+/// progress can be rebuilt from it (see the history module).
+pub fn reset_learning(config: &Config) -> Result<()> {
+    let learning_filepath = ensure_learning_dir(config);
+    persistence::reset_json(&learning_filepath, get_default_learning_file).map(|_| ())
 }
 
-/// Out of the selected question categories and according to filters, select next question.
+/// Out of the selected question categories and according to the filter, select next question.
 ///
 /// This function essentially implements the simple learning algorithm used by
 /// Funkfragenhelfer; Out of the eligible questions (i.e., the ones which are a
 /// member of the selected categories), the following is done:
 ///
 /// 1. Start with a randomly chosen index for the list of eligible questions
-/// 2. If filters are active: If the question at the current index fits to the
-///    filters, return this question. If not, raise the index by 1 (or set to 0
-///    if greater than the list length) until a filter-fitting question appears
-///    and can be returned. If no question fits to the filters, proceed with step
-///    3.
-/// 3. Without filters: Look at the question at the current index. The higher its
-///    bin, the lower the probability is chosen. If chosen, return the question.
-///    If not chosen, proceed with the next question. This is repeated until a
-///    question is chosen.
+/// 2. If the Config has a filter expression (see the filter module): If the
+///    question at the current index fulfills it, return this question. If not,
+///    raise the index by 1 (or set to 0 if greater than the list length) until
+///    a fulfilling question appears and can be returned. If no question fulfills
+///    the filter expression, proceed with step 3.
+/// 3. Without a filter, or if no question fulfills it, the question is
+///    chosen by the scheduler of the Config's scheduler mode (see the scheduler
+///    module), e.g. a due question according to the time of its last answer.
 ///
 /// ### Arguments
-/// * eligible_questions: The list of category-fitting questions from which one is chosen.
+/// * eligible_questions: The list of category-fitting questions from which one is chosen;
+///   It must not be empty.
 /// * learning: The current LearnStates, providing, e.g., the question bins.
 /// * config: The current Funkfragenhelfer Config.
+/// * rng: The random number generator for choosing the question and shuffling its
+///   answers; With a seeded generator, the same questions are chosen in the same
+///   order for the same LearnStates (and time)
 ///
 /// ### Return value
 /// * A PrintQuestion, which includes the chosen question as well as randomly shuffled answers
 ///   (as in the original Bundesnetzagentur questions set, answer A is always correct).
 pub fn get_next_print_question(
-    eligible_questions: &Vec<Question>,
-    learning: &mut LearnStates,
+    eligible_questions: &[Question],
+    learning: &LearnStates,
     config: &Config,
+    rng: &mut dyn RngCore,
 ) -> PrintQuestion {
-    // Setup randomness
-    let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
-    // Question-choosing state variables
     // The current randomly initialized questions index
-    let mut index = rng.gen_range(0..eligible_questions.len() - 1);
-    // A counter counting how many questions were already looked up
-    let mut counter = 0;
-    // Start the question-choosing loop, which runs the algorithm as described
-    // in this method's description.
-    loop {
-        // Get (or create if non-existent) the current LearnState
-        let question = &eligible_questions[index];
-        let learn_state = learning
-            .entry(String::from(&question.identifier))
-            .or_insert(LearnState::new());
-
-        // Variable choosing whether or not a question is chosen for the return value
-        let mut is_chosen = false;
-        // If we didn't already look at all questions...
-        if counter < eligible_questions.len() {
-            // ...look up if any filters apply to the current question...
-            let is_marked = (learn_state.marked) && config.prefer_marked;
-            let is_wrong = (learn_state.wrong > 0) && config.prefer_wrong;
-            let is_new = ((learn_state.wrong + learn_state.correct) == 0) && config.prefer_new;
-            // ...if yes: Choose it
-            if is_marked || is_wrong || is_new {
-                is_chosen = true;
+    let mut index = rng.gen_range(0..eligible_questions.len());
+    // Look at each question once, starting with the random index, and
+    // choose the first question which fulfills the filter expression
+    if let Some(filter) = &config.filter {
+        let current_unixtime = helper::get_current_unixtime_in_sec();
+        for _ in 0..eligible_questions.len() {
+            let question = &eligible_questions[index];
+            let learn_state = learning.get(&question.identifier);
+            if filter.evaluate(question, learn_state, current_unixtime) {
+                return PrintQuestion::new(question.clone(), rng);
             }
-        } else {
-            // If no question applies to filters, look at its bin and get the
-            // associated choosing probability (the higher the bin, the lower
-            // probability)
-            let threshold = match learn_state.current_bin {
-                1 => 5,
-                2 => 40,
-                3 => 70,
-                4 => 80,
-                5 => 85,
-                _ => 0,
-            };
-            // If the threshold is *lower* than a random value, choose the question
-            is_chosen = rng.gen_range(0..=100) > threshold;
-        }
 
-        // If a question is chosen as return value...
-        if is_chosen {
-            // ...shuffle its answers randomly...
-            let mut answer_shuffle = vec![Answer::A, Answer::B, Answer::C, Answer::D];
-            answer_shuffle.shuffle(&mut rng);
-
-            // ...and return the shuffle result together with the Question
-            return PrintQuestion {
-                question: question.clone(),
-                answer_shuffle,
-            };
+            // Raise the index or set to 0 if it is greater than the eligible questions length
+            index = (index + 1) % eligible_questions.len();
         }
-
-        // Raise the index or set to 0 if it is greater than the eligible questions length
-        index = (index + 1) % eligible_questions.len();
-        // Raise as we just looked at one more question
-        counter += 1;
     }
+
+    // If no question applies to filters, the configured scheduler chooses the question
+    let scheduler = scheduler::get_scheduler(config.scheduler_mode);
+    let index = scheduler.choose_index(eligible_questions, learning, config, rng);
+    PrintQuestion::new(eligible_questions[index].clone(), rng)
 }
 
 impl Statistics {
     /// Creates a new Statistics struct for the given questions to learn and the current LearnStates.
-    pub fn new(eligible_questions: &Vec<Question>, learning: &LearnStates) -> Statistics {
+    /// The Config determines the scheduler which is used for the number of due questions.
+    pub fn new(
+        eligible_questions: &[Question],
+        learning: &LearnStates,
+        config: &Config,
+    ) -> Statistics {
         let mut correct_answers = 0;
         let mut no_correct_answers = 0;
         let questions = eligible_questions.len();
         let mut count_per_bin = HashMap::new();
+        let scheduler = scheduler::get_scheduler(config.scheduler_mode);
+        let due_today =
+            scheduler::get_due_today_questions(eligible_questions, learning, scheduler.as_ref())
+                .len();
 
         for question in eligible_questions {
             if let Some(state) = learning.get(&question.identifier) {
@@ -338,6 +563,240 @@ impl Statistics {
             no_correct_answers,
             questions,
             count_per_bin,
+            due_today,
+        }
+    }
+}
+
+// PRIVATE FUNCTIONS SECTION //
+/// Returns the content of a new learning.json without any LearnStates.
+fn get_default_learning_file() -> LearningFile {
+    LearningFile {
+        version: LEARNING_VERSION,
+        learn_states: LearnStates::new(),
+    }
+}
+
+/// Ensures that the directory of the Config's learning.json exists
+/// and returns the learning.json's path.
+fn ensure_learning_dir(config: &Config) -> String {
+    let learning_filepath = config.get_learning_filepath();
+    if let Some(learning_dir) = Path::new(&learning_filepath).parent() {
+        helper::ensure_dir_existence(&learning_dir.to_string_lossy());
+    }
+    learning_filepath
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SchedulerMode;
+    use crate::filter::FilterExpression;
+    use crate::question::test_question;
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    /// Returns the given number of questions with the identifiers "NA000", "NA001", ...
+    fn get_questions(count: usize) -> Vec<Question> {
+        (0..count)
+            .map(|index| test_question(&format!("NA{:03}", index), &[]))
+            .collect()
+    }
+
+    #[test]
+    fn correct_answers_raise_the_bin() {
+        let config = Config::new();
+        let mut learning = LearnStates::new();
+        handle_correct_answer_at(&mut learning, "NA001", &config, 100);
+        handle_correct_answer_at(&mut learning, "NA001", &config, 200);
+
+        let learn_state = &learning["NA001"];
+        assert_eq!(learn_state.current_bin, 2);
+        assert_eq!(learn_state.correct, 2);
+        assert_eq!(learn_state.wrong, 0);
+        assert_eq!(learn_state.rounds_since_wrong, 2);
+        assert_eq!(learn_state.time_last_answer, 200);
+    }
+
+    #[test]
+    fn correct_answers_keep_the_bin_at_max_learn_bin() {
+        let mut config = Config::new();
+        config.max_learn_bin = 3;
+        let mut learning = LearnStates::new();
+        for unixtime in 0..10 {
+            handle_correct_answer_at(&mut learning, "NA001", &config, unixtime);
+        }
+
+        assert_eq!(learning["NA001"].current_bin, 3);
+        assert_eq!(learning["NA001"].correct, 10);
+    }
+
+    #[test]
+    fn wrong_answer_resets_the_bin_to_one() {
+        let config = Config::new();
+        let mut learning = LearnStates::new();
+        for unixtime in 0..4 {
+            handle_correct_answer_at(&mut learning, "NA001", &config, unixtime);
+        }
+        handle_wrong_answer_at(&mut learning, "NA001", Answer::C, 500);
+        handle_wrong_answer_at(&mut learning, "NA001", Answer::C, 600);
+
+        let learn_state = &learning["NA001"];
+        assert_eq!(learn_state.current_bin, 1);
+        assert_eq!(learn_state.correct, 4);
+        assert_eq!(learn_state.wrong, 2);
+        assert_eq!(learn_state.rounds_since_wrong, 0);
+        assert_eq!(learn_state.time_last_answer, 600);
+        assert_eq!(learn_state.wrong_answer_counts[&Answer::C], 2);
+    }
+
+    #[test]
+    fn wrong_answer_of_a_new_question_starts_in_bin_one() {
+        let mut learning = LearnStates::new();
+        handle_wrong_answer_at(&mut learning, "NA001", Answer::B, 0);
+
+        assert_eq!(learning["NA001"].current_bin, 1);
+        assert_eq!(learning["NA001"].correct, 0);
+    }
+
+    #[test]
+    fn statistics_count_the_answered_questions_per_bin() {
+        let config = Config::new();
+        let questions = get_questions(4);
+        let mut learning = LearnStates::new();
+        handle_correct_answer_at(&mut learning, "NA000", &config, 0);
+        handle_correct_answer_at(&mut learning, "NA000", &config, 0);
+        handle_correct_answer_at(&mut learning, "NA001", &config, 0);
+        handle_wrong_answer_at(&mut learning, "NA002", Answer::B, 0);
+        // Not eligible, hence not counted
+        handle_correct_answer_at(&mut learning, "VA001", &config, 0);
+
+        let statistics = Statistics::new(&questions, &learning, &config);
+        assert_eq!(statistics.questions, 4);
+        assert_eq!(statistics.correct_answers, 2);
+        assert_eq!(statistics.no_correct_answers, 1);
+        assert_eq!(statistics.count_per_bin.len(), 2);
+        assert_eq!(statistics.count_per_bin[&1], 2);
+        assert_eq!(statistics.count_per_bin[&2], 1);
+        // All answers date back to 1970, so all answered questions are due
+        assert_eq!(statistics.due_today, 3);
+    }
+
+    #[test]
+    fn single_eligible_question_is_chosen() {
+        let questions = get_questions(1);
+        let mut rng = StdRng::seed_from_u64(0);
+        for scheduler_mode in SchedulerMode::ALL {
+            let mut config = Config::new();
+            config.scheduler_mode = scheduler_mode;
+            let print_question =
+                get_next_print_question(&questions, &LearnStates::new(), &config, &mut rng);
+            assert_eq!(print_question.question.identifier, "NA000");
+        }
+    }
+
+    #[test]
+    fn same_seed_chooses_same_questions() {
+        let questions = get_questions(20);
+        let config = Config::new();
+        let get_sequence = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..10)
+                .map(|_| {
+                    let print_question =
+                        get_next_print_question(&questions, &LearnStates::new(), &config, &mut rng);
+                    (
+                        print_question.question.identifier,
+                        print_question.answer_shuffle,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(get_sequence(7), get_sequence(7));
+    }
+
+    #[test]
+    fn answer_shuffle_is_a_permutation() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let print_question = PrintQuestion::new(test_question("NA001", &[]), &mut rng);
+            let answers: HashSet<Answer> = print_question.answer_shuffle.iter().copied().collect();
+            assert_eq!(print_question.answer_shuffle.len(), 4);
+            assert_eq!(answers.len(), 4);
+        }
+    }
+
+    proptest! {
+        /// With new LearnStates, each eligible question can be chosen, with and
+        /// without a filter which all questions fulfill.
+        #[test]
+        fn every_eligible_question_can_be_chosen(
+            count in 1usize..8,
+            seed: u64,
+            scheduler_index in 0usize..4,
+            has_filter: bool,
+        ) {
+            let questions = get_questions(count);
+            let mut config = Config::new();
+            config.scheduler_mode = SchedulerMode::ALL[scheduler_index];
+            if has_filter {
+                config.filter = Some(FilterExpression::And(Vec::new()));
+            }
+            let learning = LearnStates::new();
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            let mut chosen_identifiers = HashSet::new();
+            for _ in 0..count * 100 {
+                let print_question = get_next_print_question(&questions, &learning, &config, &mut rng);
+                chosen_identifiers.insert(print_question.question.identifier);
+            }
+            prop_assert_eq!(chosen_identifiers.len(), count);
+        }
+
+        /// Whatever the LearnStates, the scheduler and the filter are, an eligible
+        /// question is chosen (i.e. the selection terminates), which fulfills the
+        /// filter if any eligible question does.
+        #[test]
+        fn selection_terminates_with_an_eligible_question(
+            answers in prop::collection::vec(prop::collection::vec(any::<bool>(), 0..12), 1..8),
+            marked in prop::collection::vec(any::<bool>(), 8),
+            seed: u64,
+            scheduler_index in 0usize..4,
+            max_learn_bin in 1u64..8,
+            has_filter: bool,
+        ) {
+            let questions = get_questions(answers.len());
+            let mut config = Config::new();
+            config.scheduler_mode = SchedulerMode::ALL[scheduler_index];
+            config.max_learn_bin = max_learn_bin;
+            if has_filter {
+                config.filter = Some(FilterExpression::Marked);
+            }
+            let mut learning = LearnStates::new();
+            for (question, question_answers) in questions.iter().zip(&answers) {
+                for (unixtime, is_correct) in question_answers.iter().enumerate() {
+                    if *is_correct {
+                        handle_correct_answer_at(&mut learning, &question.identifier, &config, unixtime as u64);
+                    } else {
+                        handle_wrong_answer_at(&mut learning, &question.identifier, Answer::B, unixtime as u64);
+                    }
+                }
+                prop_assert!(learning.get(&question.identifier).map_or(0, |learn_state| learn_state.current_bin) <= max_learn_bin);
+            }
+            for (question, is_marked) in questions.iter().zip(&marked) {
+                learning.entry(question.identifier.clone()).or_default().marked = *is_marked;
+            }
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            let print_question = get_next_print_question(&questions, &learning, &config, &mut rng);
+            let identifier = &print_question.question.identifier;
+            prop_assert!(questions.iter().any(|question| &question.identifier == identifier));
+            if has_filter && marked[..questions.len()].contains(&true) {
+                prop_assert!(learning[identifier].marked);
+            }
         }
     }
 }
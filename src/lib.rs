@@ -0,0 +1,40 @@
+//! The Funkfragenhelfer library, which contains everything besides the front ends.
+//!
+//! With it, own tools (e.g. a leaderboard, a chat bot or a web front end) can be
+//! built on Funkfragenhelfer's question catalog and learning algorithm. The main
+//! types are:
+//! * question::Question: A question of the catalog, which is loaded with
+//!   catalog::load_catalog()
+//! * learning::LearnState: The learning progress of a single question; All
+//!   LearnStates are loaded with learning::load_learning()
+//! * learning::PrintQuestion: A question together with its shuffled answers,
+//!   as it is shown to the user
+//! * learning::Statistics: The learning progress of a set of questions
+//! * config::Config: The user's configuration, which is loaded with
+//!   config::load_config()
+//! * session::Session: A learning session, i.e. the practice loop which a front
+//!   end drives
+//!
+//! Funkfragenhelfer's own front ends (the egui GUI, the command-line mode and
+//! the terminal UI) are part of the binary and only use this library.
+
+// Load Funkfragenhelfer modules
+pub mod catalog;
+pub mod config;
+pub mod deck;
+pub mod error;
+pub mod exam;
+pub mod filter;
+mod helper;
+pub mod history;
+pub mod learning;
+pub mod math;
+pub mod migration;
+pub mod paths;
+pub mod persistence;
+pub mod picture;
+pub mod profiles;
+pub mod question;
+pub mod scheduler;
+pub mod search;
+pub mod session;
@@ -1,26 +1,257 @@
 //! Main file of Funkfragenhelfer.
 //!
-//! Here, all Funkfragenhelfer modules are loaded and the GUI is started.
-//! For more about the specific modules, look up their comments. Note that
-//! only the "GUI" module contains egui code, all other modules are GUI-framework
-//! agnostic.
+//! Here, the front end modules are loaded and the GUI (or, with "--cli" or
+//! "--tui", a terminal front end) is started. All other modules are part of the
+//! Funkfragenhelfer library (see lib.rs), on which the front ends are built. For
+//! more about the specific modules, look up their comments. Note that only the
+//! "GUI" module contains egui code and only the "TUI" module contains ratatui
+//! code, all library modules are GUI-framework agnostic.
 
-// Make sure that no console occurs under Windows in the release build
+// Make sure that no console occurs under Windows in the release build (the
+// terminal front ends open their own console, see allocate_console())
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-// Load Funkfragenhelfer modules
-mod config;
+// Load Funkfragenhelfer front end modules
+mod arguments;
+mod cli;
 mod gui;
-mod helper;
-mod learning;
-mod question;
+mod tui;
+
+// IMPORTS SECTION //
+use arguments::Frontend;
+use funkfragenhelfer::error::{ErrorAction, FfhError, Result};
+use funkfragenhelfer::persistence::Loaded;
+use funkfragenhelfer::session::Session;
+use funkfragenhelfer::{
+    catalog, config, history, learning, paths, picture, profiles, question, scheduler,
+};
+use gui::GuiExit;
 
 // Start Funkfragenhelger
 fn main() {
-    let config = config::load_config();
-    let learn_states: learning::LearnStates = learning::load_learning();
-    let questions_json_str = helper::read_filetext("./resources/ffh_questions.json");
-    let questions: Vec<question::Question> = serde_json::from_str(&questions_json_str).unwrap();
+    let arguments = match arguments::parse_arguments(std::env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, arguments::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if arguments.show_help {
+        println!("{}", arguments::USAGE);
+        return;
+    }
+    if arguments.frontend.is_terminal() {
+        allocate_console();
+    }
+
+    // In the terminal front ends, errors on startup are handled on the command
+    // line instead of in the GUI's error screen
+    let ask_error_action: fn(&FfhError, &[ErrorAction]) -> ErrorAction =
+        if arguments.frontend.is_terminal() {
+            cli::ask_error_action
+        } else {
+            gui::run_error_screen
+        };
+
+    // The profile picker is only shown in the GUI if there is more than one
+    // profile and no profile was given on the command line
+    let data_dir = paths::get_data_dir(arguments.data_dir.as_deref());
+    let profile_names = match profiles::get_profile_names(&data_dir) {
+        Ok(profile_names) => profile_names,
+        Err(e) => {
+            eprintln!("{}", e);
+            ask_error_action(&e, &[]);
+            std::process::exit(1);
+        }
+    };
+    let mut profile_name = match &arguments.profile {
+        Some(profile_name) if !profile_names.contains(profile_name) => {
+            eprintln!("Unknown profile '{}'", profile_name);
+            std::process::exit(2);
+        }
+        Some(profile_name) => profile_name.clone(),
+        None => String::from(profiles::DEFAULT_PROFILE_NAME),
+    };
+    let mut show_profile_picker =
+        !arguments.frontend.is_terminal() && arguments.profile.is_none() && profile_names.len() > 1;
+
+    loop {
+        if show_profile_picker {
+            match gui::run_profile_picker(&data_dir) {
+                Some(chosen_profile_name) => profile_name = chosen_profile_name,
+                None => return,
+            }
+        }
+        let profile_dir = profiles::get_profile_dir(&data_dir, &profile_name);
+
+        // Load the config and the learning progress from the profile's data dir, and
+        // collect the warnings about restored files so that they can be shown in the GUI
+        let mut warnings = Vec::new();
+        let Some(config) = load_or_ask_user(
+            || config::load_config(&profile_dir),
+            || config::repair_config(&profile_dir),
+            || config::reset_config(&profile_dir),
+            None,
+            ask_error_action,
+            &mut warnings,
+        ) else {
+            return;
+        };
+        let Some(mut learn_states) = load_or_ask_user(
+            || learning::load_learning(&config),
+            || learning::repair_learning(&config),
+            || learning::reset_learning(&config),
+            Some(&|| history::rebuild_learning(&config)),
+            ask_error_action,
+            &mut warnings,
+        ) else {
+            return;
+        };
+        for warning in &warnings {
+            println!("{}", warning);
+        }
+        scheduler::migrate_learn_states(&mut learn_states);
+
+        // The question catalog is shipped with Funkfragenhelfer, so it
+        // can neither be repaired nor reset
+        let catalog = match catalog::load_catalog(&config.get_questions_filepath()) {
+            Ok(catalog) => catalog,
+            Err(e) => {
+                eprintln!("{}", e);
+                ask_error_action(&e, &[]);
+                std::process::exit(1);
+            }
+        };
+        let questions: Vec<question::Question> = catalog.get_questions();
+
+        // Report pictures which are referenced by questions but whose files are missing
+        let picture_index = picture::PictureIndex::new(&config.get_pictures_dir());
+        for missing_picture in picture_index.get_missing_pictures(&questions) {
+            println!(
+                "Missing picture '{}' of question {}",
+                missing_picture.picture, missing_picture.question_identifier
+            );
+        }
+
+        // The learning session, which is driven by the front end
+        let Some(mut session) = Session::new(config, learn_states, questions, arguments.seed)
+        else {
+            eprintln!("The configuration does not select any question");
+            std::process::exit(1);
+        };
+
+        // The terminal front ends only use the profile they were started with
+        if arguments.frontend.is_terminal() {
+            let result = match arguments.frontend {
+                Frontend::Tui => tui::run(&mut session, &picture_index, &warnings),
+                _ => cli::run(
+                    &mut session,
+                    &picture_index,
+                    &mut std::io::stdin().lock(),
+                    &mut std::io::stdout(),
+                ),
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+
+        match gui::run(
+            session,
+            catalog.metadata,
+            picture_index,
+            warnings,
+            profile_name.clone(),
+        ) {
+            Ok(GuiExit::SwitchProfile) => show_profile_picker = true,
+            Ok(GuiExit::Quit) => return,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Opens a console window for the terminal front ends under Windows. As the
+/// release build is a Windows GUI application (see the top of this file), it
+/// has no console, so that neither "--cli" nor "--tui" could read or write
+/// anything otherwise. A console of the calling terminal is not used, as the
+/// terminal does not wait for GUI applications and would read the same input.
+#[cfg(all(windows, not(debug_assertions)))]
+fn allocate_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AllocConsole() -> i32;
+    }
+    // SAFETY: AllocConsole has no preconditions; It creates a new console and
+    // sets the standard handles to it, or fails if there already is a console
+    unsafe {
+        AllocConsole();
+    }
+}
+
+/// Does nothing, as the process already has a console on other platforms and
+/// in debug builds (see above).
+#[cfg(not(all(windows, not(debug_assertions))))]
+fn allocate_console() {}
 
-    gui::run(config, learn_states, questions).unwrap();
+/// Loads a user data file. If this fails, the user is asked (in the startup error
+/// screen or on the command line) whether to repair or reset the file (if possible),
+/// whereupon the file is loaded again.
+///
+/// ### Arguments
+/// * load: Loads the file
+/// * repair: Repairs the file (see the persistence module)
+/// * reset: Resets the file to its default content
+/// * rebuild: Rebuilds the file in another way (i.e., the learning.json from the
+///   answer history), returning a message for the user, or None if not possible
+/// * ask_error_action: Asks the user which of the given actions shall be applied
+/// * warnings: The list into which warnings about restored files are pushed
+///
+/// ### Return value
+/// * The loaded value, or None if the user chose to quit
+fn load_or_ask_user<T>(
+    load: impl Fn() -> Result<Loaded<T>>,
+    repair: impl Fn() -> Result<()>,
+    reset: impl Fn() -> Result<()>,
+    rebuild: Option<&dyn Fn() -> Result<String>>,
+    ask_error_action: fn(&FfhError, &[ErrorAction]) -> ErrorAction,
+    warnings: &mut Vec<String>,
+) -> Option<T> {
+    let mut result = load();
+    loop {
+        let error = match result {
+            Ok(loaded) => {
+                warnings.extend(loaded.warning);
+                return Some(loaded.value);
+            }
+            Err(error) => error,
+        };
+        eprintln!("{}", error);
+        let mut actions = match error {
+            FfhError::Parse { .. } => vec![ErrorAction::Repair, ErrorAction::Reset],
+            FfhError::SchemaVersion { .. } => vec![ErrorAction::Reset],
+            FfhError::Io { .. }
+            | FfhError::MissingAsset { .. }
+            | FfhError::InvalidAnswer { .. } => Vec::new(),
+        };
+        if rebuild.is_some() && !actions.is_empty() {
+            actions.push(ErrorAction::Rebuild);
+        }
+        result = match ask_error_action(&error, &actions) {
+            ErrorAction::Repair => repair().and_then(|_| load()),
+            ErrorAction::Reset => reset().and_then(|_| load()),
+            ErrorAction::Rebuild => match rebuild {
+                Some(rebuild) => rebuild().and_then(|message| {
+                    warnings.push(message);
+                    load()
+                }),
+                None => load(),
+            },
+            ErrorAction::Quit => return None,
+        };
+    }
 }
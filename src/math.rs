@@ -0,0 +1,735 @@
+//! This module contains the math layout of Funkfragenhelfer.
+//!
+//! The question and answer texts of the Bundesnetzagentur catalog contain
+//! LaTeX formulas between dollar signs, e.g. "$10^{0,5}$" or
+//! "$U_1 = \frac{U_2}{5}$". Here, such texts are split into plain text and
+//! formula parts, and the formulas are parsed into a small layout tree of
+//! MathNode instances (superscripts, subscripts, fractions, roots, ...).
+//! A user interface can typeset this tree. For contexts that cannot
+//! typeset formulas, this module also provides a plain-Unicode conversion
+//! (e.g. "$10^{-6}$" becomes "10⁻⁶").
+//!
+//! Only the LaTeX subset which occurs in the catalog (and a bit more) is
+//! supported. Unknown commands are kept as their name without the backslash,
+//! so that no text is lost. A few commands also occur in the plain text outside
+//! of dollar signs (e.g. "4 \mOhm" or "\glqq"); These are replaced by their
+//! Unicode text, whereas other backslashes in plain text are kept.
+
+// IMPORTS SECTION //
+use std::iter::Peekable;
+use std::str::Chars;
+
+// ENUM SECTION //
+/// A part of a question or answer text, which is either plain
+/// text or a parsed formula.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TextPart {
+    /// Plain text outside of dollar signs
+    Text(String),
+    /// The parsed formula of a "$...$" segment
+    Math(Vec<MathNode>),
+}
+
+/// A node of a parsed formula.
+#[derive(Clone, PartialEq, Debug)]
+pub enum MathNode {
+    /// Upright text such as digits, operators, Greek letters or "\textrm" content
+    Symbol(String),
+    /// Italic text, i.e. Latin letters used as variables
+    Variable(String),
+    /// A superscript (e.g. the exponent in "10^{-6}")
+    Superscript(Vec<MathNode>),
+    /// A subscript (e.g. the index in "R_1")
+    Subscript(Vec<MathNode>),
+    /// A fraction with its numerator and denominator
+    Fraction(Vec<MathNode>, Vec<MathNode>),
+    /// A square root with its radicand
+    Root(Vec<MathNode>),
+}
+
+// CONSTANTS SECTION //
+/// Characters with a Unicode superscript form (the degree sign is
+/// already a raised character and is kept as it is)
+const SUPERSCRIPTS: [(char, char); 18] = [
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('n', 'ⁿ'),
+    ('i', 'ⁱ'),
+    ('°', '°'),
+];
+
+/// Characters with a Unicode subscript form
+const SUBSCRIPTS: [(char, char); 32] = [
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('h', 'ₕ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('p', 'ₚ'),
+    ('r', 'ᵣ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+    ('u', 'ᵤ'),
+    ('v', 'ᵥ'),
+    ('x', 'ₓ'),
+];
+
+// PRIVATE FUNCTION SECTION //
+/// Returns the Unicode text of the given LaTeX symbol command (without
+/// backslash), or None if the command is no known symbol.
+fn get_command_symbol(command: &str) -> Option<&'static str> {
+    let symbol = match command {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "eta" => "η",
+        "theta" => "θ",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "phi" | "varphi" => "φ",
+        "omega" => "ω",
+        "Delta" => "Δ",
+        "Phi" => "Φ",
+        "Omega" => "Ω",
+        "cdot" => " · ",
+        "times" => " × ",
+        "approx" => " ≈ ",
+        "ll" => " ≪ ",
+        "gg" => " ≫ ",
+        "leq" | "le" => " ≤ ",
+        "geq" | "ge" => " ≥ ",
+        "neq" | "ne" => " ≠ ",
+        "pm" => "±",
+        "infty" => "∞",
+        "circ" => "°",
+        "degree" => "°",
+        "%" => "%",
+        "{" => "{",
+        "}" => "}",
+        "$" => "$",
+        "," | ";" | ":" | " " | "quad" => " ",
+        _ => return get_text_command_symbol(command),
+    };
+    Some(symbol)
+}
+
+/// Returns the Unicode text of the given LaTeX command (without backslash)
+/// which may also occur in plain text, or None if the command is no such
+/// command. These are the units and quotation marks of the catalog.
+fn get_text_command_symbol(command: &str) -> Option<&'static str> {
+    let symbol = match command {
+        "Ohm" => "Ω",
+        "milliOhm" | "mOhm" => "mΩ",
+        "kiloOhm" | "kOhm" => "kΩ",
+        "megaOhm" | "MOhm" => "MΩ",
+        "glqq" => "„",
+        "grqq" => "“",
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+/// Returns the given letter with the accent of the given LaTeX accent command
+/// (e.g. "^" for "\^i"), or None if there is no such accented letter.
+fn get_accented_char(accent: char, c: char) -> Option<char> {
+    const ACCENTED_CHARS: [(char, &str, &str); 3] = [
+        ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+        ('\'', "aeiouAEIOU", "áéíóúÁÉÍÓÚ"),
+        ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+    ];
+    let (_, letters, accented_letters) = ACCENTED_CHARS
+        .iter()
+        .find(|(accent_command, _, _)| *accent_command == accent)?;
+    let index = letters.chars().position(|letter| letter == c)?;
+    accented_letters.chars().nth(index)
+}
+
+/// Reads the accent and the letter of an accent command whose backslash was just
+/// read, e.g. "^i" or "^{i}", and returns the accented letter. If there is no
+/// such accent command, nothing is read and None is returned.
+fn read_accented_char(chars: &mut Peekable<Chars>) -> Option<char> {
+    let mut lookahead = chars.clone();
+    let accent = lookahead.next()?;
+    let is_group = lookahead.peek() == Some(&'{');
+    if is_group {
+        lookahead.next();
+    }
+    let accented_char = get_accented_char(accent, lookahead.next()?)?;
+    if is_group && lookahead.next() != Some('}') {
+        return None;
+    }
+    *chars = lookahead;
+    Some(accented_char)
+}
+
+/// Replaces the commands of the given plain text (outside of dollar signs) which
+/// are known by get_text_command_symbol(), as well as accent commands such as
+/// "\^i" or "\^{i}". Other backslashes are kept.
+fn replace_text_commands(text: &str) -> String {
+    let mut replaced_text = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            replaced_text.push(c);
+            continue;
+        }
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if !c.is_ascii_alphabetic() {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+        if name.is_empty() {
+            if let Some(accented_char) = read_accented_char(&mut chars) {
+                replaced_text.push(accented_char);
+                continue;
+            }
+        }
+        match get_text_command_symbol(&name) {
+            Some(symbol) => {
+                replaced_text.push_str(symbol);
+                // An empty group ends a command as in "\glqq{}Text"
+                if chars.peek() == Some(&'{') {
+                    let mut lookahead = chars.clone();
+                    lookahead.next();
+                    if lookahead.peek() == Some(&'}') {
+                        lookahead.next();
+                        chars = lookahead;
+                    }
+                }
+            }
+            None => {
+                replaced_text.push('\\');
+                replaced_text.push_str(&name);
+            }
+        }
+    }
+    replaced_text
+}
+
+/// Appends the given text as Symbol to the given nodes, merging it with
+/// a directly preceding Symbol.
+fn push_symbol(nodes: &mut Vec<MathNode>, text: &str) {
+    if let Some(MathNode::Symbol(last_text)) = nodes.last_mut() {
+        last_text.push_str(text);
+    } else {
+        nodes.push(MathNode::Symbol(text.to_string()));
+    }
+}
+
+/// Appends the given text as Variable to the given nodes, merging it with
+/// a directly preceding Variable.
+fn push_variable(nodes: &mut Vec<MathNode>, text: &str) {
+    if let Some(MathNode::Variable(last_text)) = nodes.last_mut() {
+        last_text.push_str(text);
+    } else {
+        nodes.push(MathNode::Variable(text.to_string()));
+    }
+}
+
+/// Turns all Variables of the given nodes into (upright) Symbols, as done
+/// by "\mathrm".
+fn make_upright(nodes: Vec<MathNode>) -> Vec<MathNode> {
+    let mut upright_nodes = Vec::new();
+    for node in nodes {
+        match node {
+            MathNode::Symbol(text) | MathNode::Variable(text) => {
+                push_symbol(&mut upright_nodes, &text)
+            }
+            MathNode::Superscript(inner) => {
+                upright_nodes.push(MathNode::Superscript(make_upright(inner)))
+            }
+            MathNode::Subscript(inner) => {
+                upright_nodes.push(MathNode::Subscript(make_upright(inner)))
+            }
+            MathNode::Fraction(numerator, denominator) => upright_nodes.push(MathNode::Fraction(
+                make_upright(numerator),
+                make_upright(denominator),
+            )),
+            MathNode::Root(inner) => upright_nodes.push(MathNode::Root(make_upright(inner))),
+        }
+    }
+    upright_nodes
+}
+
+/// Skips all whitespace at the current position.
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// Reads the raw text of a "{...}" group (as used by "\text{...}"), or a
+/// single character if no group follows.
+fn read_raw_group(chars: &mut Peekable<Chars>) -> String {
+    skip_whitespace(chars);
+    if chars.peek() != Some(&'{') {
+        return chars.next().map(String::from).unwrap_or_default();
+    }
+    chars.next();
+    let mut depth = 1;
+    let mut text = String::new();
+    for c in chars.by_ref() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        text.push(c);
+    }
+    text
+}
+
+/// Reads the name of a command directly after its backslash, i.e. either
+/// a sequence of letters or a single other character.
+fn read_command_name(chars: &mut Peekable<Chars>) -> String {
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_alphabetic() {
+            break;
+        }
+        name.push(c);
+        chars.next();
+    }
+    if name.is_empty() {
+        if let Some(c) = chars.next() {
+            name.push(c);
+        }
+    }
+    name
+}
+
+/// Parses the argument of a command or of "^" and "_", which is either a
+/// "{...}" group, a command or a single character.
+fn parse_argument(chars: &mut Peekable<Chars>) -> Vec<MathNode> {
+    skip_whitespace(chars);
+    let mut nodes = Vec::new();
+    match chars.peek() {
+        Some('{') => {
+            chars.next();
+            nodes = parse_sequence(chars, true);
+        }
+        Some('\\') => {
+            chars.next();
+            parse_command(chars, &mut nodes);
+        }
+        Some(_) => parse_char(chars.next().unwrap(), &mut nodes),
+        None => {}
+    }
+    nodes
+}
+
+/// Parses the command whose backslash was just read and appends its
+/// node(s) to the given nodes.
+fn parse_command(chars: &mut Peekable<Chars>, nodes: &mut Vec<MathNode>) {
+    let name = read_command_name(chars);
+    match name.as_str() {
+        "text" | "textrm" | "textup" | "mbox" => push_symbol(nodes, &read_raw_group(chars)),
+        "mathrm" | "rm" | "operatorname" => {
+            let argument = parse_argument(chars);
+            nodes.extend(make_upright(argument));
+        }
+        "frac" | "dfrac" | "tfrac" => {
+            let numerator = parse_argument(chars);
+            let denominator = parse_argument(chars);
+            nodes.push(MathNode::Fraction(numerator, denominator));
+        }
+        "sqrt" => {
+            skip_whitespace(chars);
+            // An optional root index, e.g. "\sqrt[3]{x}", is shown as superscript
+            if chars.peek() == Some(&'[') {
+                chars.next();
+                let mut index = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    index.push(c);
+                }
+                nodes.push(MathNode::Superscript(parse_math(&index)));
+            }
+            let radicand = parse_argument(chars);
+            nodes.push(MathNode::Root(radicand));
+        }
+        "left" | "right" | "big" | "Big" | "bigl" | "bigr" => {
+            // Only the delimiter itself is shown; "." means no delimiter
+            skip_whitespace(chars);
+            if chars.peek() == Some(&'.') {
+                chars.next();
+            }
+        }
+        _ => match get_command_symbol(&name) {
+            Some(symbol) => push_symbol(nodes, symbol),
+            None => push_symbol(nodes, &name),
+        },
+    }
+}
+
+/// Parses a single ordinary character and appends its node to the given nodes.
+fn parse_char(c: char, nodes: &mut Vec<MathNode>) {
+    if c.is_ascii_alphabetic() {
+        push_variable(nodes, &c.to_string());
+    } else if matches!(c, '=' | '<' | '>') {
+        push_symbol(nodes, &format!(" {} ", c));
+    } else {
+        push_symbol(nodes, &c.to_string());
+    }
+}
+
+/// Parses the formula until its end or, if until_brace is true, until the
+/// closing brace of the current group.
+fn parse_sequence(chars: &mut Peekable<Chars>, until_brace: bool) -> Vec<MathNode> {
+    let mut nodes = Vec::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '}' if until_brace => break,
+            '}' => {}
+            '{' => {
+                let group = parse_sequence(chars, true);
+                nodes.extend(group);
+            }
+            '^' => {
+                let argument = parse_argument(chars);
+                nodes.push(MathNode::Superscript(argument));
+            }
+            '_' => {
+                let argument = parse_argument(chars);
+                nodes.push(MathNode::Subscript(argument));
+            }
+            '\\' => parse_command(chars, &mut nodes),
+            // As in LaTeX, whitespace in formulas is ignored
+            c if c.is_whitespace() => {}
+            c => parse_char(c, &mut nodes),
+        }
+    }
+    nodes
+}
+
+/// Returns the given text with all characters replaced by the given
+/// Unicode forms, or None if a character has no such form.
+fn map_chars(text: &str, char_map: &[(char, char)]) -> Option<String> {
+    text.chars()
+        .map(|c| {
+            let c = if c == '−' { '-' } else { c };
+            char_map
+                .iter()
+                .find(|(from, _)| *from == c)
+                .map(|(_, to)| *to)
+        })
+        .collect()
+}
+
+/// Puts the given text into parentheses if it consists of more than one term,
+/// so that it stays unambiguous after a "/", "√", "^" or "_".
+fn wrap_term(text: &str) -> String {
+    let is_single_term = !text
+        .chars()
+        .any(|c| c.is_whitespace() || "+-−±·×/=≈<>".contains(c));
+    if is_single_term && !text.is_empty() {
+        text.to_string()
+    } else {
+        format!("({})", text.trim())
+    }
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Splits the given question or answer text into plain text parts and
+/// parsed formula parts, which are enclosed in "$...$" or (as display
+/// formula) in "\[...\]". A "$" or "\[" without closing counterpart is
+/// kept as plain text. In plain text parts, the known text commands (e.g. "\mOhm")
+/// are replaced by their Unicode text.
+pub fn parse_text(text: &str) -> Vec<TextPart> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    loop {
+        // The first formula delimiter together with its closing counterpart
+        let delimiters = [("$", "$"), ("\\[", "\\]")]
+            .into_iter()
+            .filter_map(|(opening, closing)| Some((rest.find(opening)?, opening, closing)))
+            .min_by_key(|(start, _, _)| *start);
+        let Some((start, opening, closing)) = delimiters else {
+            break;
+        };
+        let latex_start = start + opening.len();
+        let Some(length) = rest[latex_start..].find(closing) else {
+            break;
+        };
+        if start > 0 {
+            parts.push(TextPart::Text(replace_text_commands(&rest[..start])));
+        }
+        let latex = &rest[latex_start..latex_start + length];
+        parts.push(TextPart::Math(parse_math(latex)));
+        rest = &rest[latex_start + length + closing.len()..];
+    }
+    if !rest.is_empty() {
+        parts.push(TextPart::Text(replace_text_commands(rest)));
+    }
+    parts
+}
+
+/// Parses the given LaTeX formula (without the surrounding dollar signs).
+pub fn parse_math(latex: &str) -> Vec<MathNode> {
+    let mut chars = latex.chars().peekable();
+    parse_sequence(&mut chars, false)
+}
+
+/// Returns whether or not the given text contains any formula.
+pub fn contains_math(text: &str) -> bool {
+    parse_text(text)
+        .iter()
+        .any(|part| matches!(part, TextPart::Math(_)))
+}
+
+/// Converts the given parsed formula into plain Unicode text, using Unicode
+/// superscript and subscript characters where possible.
+pub fn nodes_to_unicode(nodes: &[MathNode]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            MathNode::Symbol(symbol) => text.push_str(symbol),
+            MathNode::Variable(variable) => text.push_str(variable),
+            MathNode::Superscript(inner) => {
+                let inner_text = nodes_to_unicode(inner);
+                match map_chars(&inner_text, &SUPERSCRIPTS) {
+                    Some(superscript) => text.push_str(&superscript),
+                    None => text.push_str(&format!("^{}", wrap_term(&inner_text))),
+                }
+            }
+            MathNode::Subscript(inner) => {
+                let inner_text = nodes_to_unicode(inner);
+                match map_chars(&inner_text, &SUBSCRIPTS) {
+                    Some(subscript) => text.push_str(&subscript),
+                    None => text.push_str(&format!("_{}", wrap_term(&inner_text))),
+                }
+            }
+            MathNode::Fraction(numerator, denominator) => text.push_str(&format!(
+                "{}/{}",
+                wrap_term(&nodes_to_unicode(numerator)),
+                wrap_term(&nodes_to_unicode(denominator))
+            )),
+            MathNode::Root(inner) => {
+                text.push_str(&format!("√{}", wrap_term(&nodes_to_unicode(inner))))
+            }
+        }
+    }
+    text
+}
+
+/// Converts the given question or answer text into plain Unicode text, i.e.
+/// all formulas are replaced by their Unicode representation (see
+/// nodes_to_unicode()).
+pub fn to_unicode(text: &str) -> String {
+    parse_text(text)
+        .iter()
+        .map(|part| match part {
+            TextPart::Text(plain_text) => plain_text.clone(),
+            TextPart::Math(nodes) => nodes_to_unicode(nodes),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a Symbol node with the given text.
+    fn symbol(text: &str) -> MathNode {
+        MathNode::Symbol(text.to_string())
+    }
+
+    /// Returns a Variable node with the given text.
+    fn variable(text: &str) -> MathNode {
+        MathNode::Variable(text.to_string())
+    }
+
+    #[test]
+    fn text_is_split_into_text_and_math_parts() {
+        assert_eq!(
+            parse_text("Wie groß ist $x$?"),
+            [
+                TextPart::Text(String::from("Wie groß ist ")),
+                TextPart::Math(vec![variable("x")]),
+                TextPart::Text(String::from("?")),
+            ]
+        );
+        assert_eq!(
+            parse_text(r"Formel: \[d = 1\]"),
+            [
+                TextPart::Text(String::from("Formel: ")),
+                TextPart::Math(vec![variable("d"), symbol(" = 1")]),
+            ]
+        );
+        assert!(contains_math("$1$"));
+        assert!(!contains_math("1 A"));
+    }
+
+    #[test]
+    fn unbalanced_dollar_is_kept_as_text() {
+        assert_eq!(
+            parse_text("$a$ kostet 5 $"),
+            [
+                TextPart::Math(vec![variable("a")]),
+                TextPart::Text(String::from(" kostet 5 $")),
+            ]
+        );
+        assert_eq!(to_unicode("nur $ ein Dollar"), "nur $ ein Dollar");
+    }
+
+    #[test]
+    fn dfrac_is_a_fraction() {
+        assert_eq!(
+            parse_math(r"\dfrac{U}{R}"),
+            [MathNode::Fraction(vec![variable("U")], vec![variable("R")])]
+        );
+        assert_eq!(to_unicode(r"$\dfrac{U_1}{5}$"), "U₁/5");
+        assert_eq!(to_unicode(r"$\frac{1}{a + b}$"), "1/(a+b)");
+    }
+
+    #[test]
+    fn sqrt_is_a_root() {
+        assert_eq!(
+            parse_math(r"\sqrt{P \cdot R}"),
+            [MathNode::Root(vec![
+                variable("P"),
+                symbol(" · "),
+                variable("R")
+            ])]
+        );
+        assert_eq!(to_unicode(r"$\sqrt{2}$"), "√2");
+        assert_eq!(to_unicode(r"$\sqrt[3]{x}$"), "³√x");
+    }
+
+    #[test]
+    fn scripts_with_textrm_are_upright() {
+        assert_eq!(
+            parse_math(r"R_{\textrm{DSon}}"),
+            [variable("R"), MathNode::Subscript(vec![symbol("DSon")])]
+        );
+        assert_eq!(to_unicode(r"$P_{\textrm{L}}$"), "P_L");
+        assert_eq!(to_unicode(r"$R_{1}$"), "R₁");
+        assert_eq!(to_unicode(r"$10^{-6}$"), "10⁻⁶");
+        assert_eq!(to_unicode(r"$10^{0,5}$"), "10^0,5");
+        assert_eq!(to_unicode(r"$f_{\textrm{res}}$"), "fᵣₑₛ");
+    }
+
+    #[test]
+    fn left_and_right_keep_only_the_delimiters() {
+        assert_eq!(
+            to_unicode(r"$\left(\eta = \dfrac{P_{\textrm{L}}}{P_{\textrm{IN}}}\right)$"),
+            "(η = P_L/P_IN)"
+        );
+        assert_eq!(to_unicode(r"$\left. x \right)$"), "x)");
+    }
+
+    #[test]
+    fn greek_letters_and_operators_are_unicode() {
+        assert_eq!(
+            to_unicode(r"$\lambda \cdot \pi \approx 2 \Omega$"),
+            "λ · π ≈ 2Ω"
+        );
+        assert_eq!(to_unicode(r"$\varphi$ und $\Delta\varepsilon$"), "φ und Δε");
+        assert_eq!(to_unicode(r"$\mathrm{d}x$"), "dx");
+    }
+
+    #[test]
+    fn unknown_commands_keep_their_name() {
+        assert_eq!(to_unicode(r"$\foo{x}$"), "foox");
+    }
+
+    #[test]
+    fn text_commands_are_replaced_outside_of_formulas() {
+        assert_eq!(to_unicode(r"ca. 3,41 \milliOhm"), "ca. 3,41 mΩ");
+        assert_eq!(
+            to_unicode(r"$R_{\textrm{DSon}}$ = 4 \mOhm bei"),
+            "R_DSon = 4 mΩ bei"
+        );
+        assert_eq!(to_unicode(r"$R_{1}$ = 4,7 \kiloOhm"), "R₁ = 4,7 kΩ");
+        assert_eq!(to_unicode(r#"bei \glqq?" ist"#), "bei „?\" ist");
+        assert_eq!(to_unicode(r"\glqq{}Text\grqq{}"), "„Text“");
+        assert_eq!(to_unicode(r"a \b c"), r"a \b c");
+        assert_eq!(to_unicode(r"s'il vous pla\^it"), "s'il vous plaît");
+        assert_eq!(to_unicode(r"\'{e}t\`e \^x"), r"étè \^x");
+        assert_eq!(to_unicode(r"$\kiloOhm$"), "kΩ");
+    }
+
+    #[test]
+    fn formula_free_text_with_text_commands_is_replaced() {
+        let text = r"ca. 3,41 \milliOhm bei \glqq{}Pr\'{e}sence\grqq{}";
+        assert!(!contains_math(text));
+        assert_eq!(
+            parse_text(text),
+            [TextPart::Text(String::from("ca. 3,41 mΩ bei „Présence“"))]
+        );
+        assert_eq!(to_unicode(text), "ca. 3,41 mΩ bei „Présence“");
+    }
+
+    #[test]
+    fn shipped_catalog_has_no_raw_commands_left() {
+        let catalog =
+            crate::catalog::load_catalog("./resources/fragenkatalog/fragenkatalog.json").unwrap();
+        for question in catalog.get_questions() {
+            for text in [
+                &question.question,
+                &question.answer_a,
+                &question.answer_b,
+                &question.answer_c,
+                &question.answer_d,
+            ] {
+                let unicode_text = to_unicode(text);
+                assert!(
+                    !unicode_text.contains('\\'),
+                    "{}: {}",
+                    question.identifier,
+                    unicode_text
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! This module contains the schema versions of Funkfragenhelfer's persisted
+//! JSON files (i.e., "config.json" and "learning.json") and the migrations
+//! which upgrade older files to the current version.
+//!
+//! Each file has a "version" field. Files of versions before the introduction
+//! of this field count as version 0. A migration is a function which upgrades
+//! the raw JSON value of a file from one version to the next one. When a file is
+//! loaded, all migrations from the file's version up to the current version are
+//! applied in order (see the persistence module, which also keeps a backup of
+//! the original file).
+//!
+//! To change a persisted format:
+//! 1. If the change cannot be expressed with serde defaults alone (e.g. a field
+//!    is renamed or its meaning changes), add a migration function to the
+//!    file's migrations list.
+//! 2. Raise the file's version constant accordingly.
+
+// IMPORTS SECTION //
+use crate::config::DEFAULT_QUESTIONS_FILEPATH;
+use crate::error::{FfhError, Result};
+use serde_json::{json, Map, Value};
+
+// CONSTANTS SECTION //
+/// Current version of the config.json format
+pub const CONFIG_VERSION: u64 = 2;
+/// Current version of the learning.json format
+pub const LEARNING_VERSION: u64 = 1;
+
+/// Default questions path of config.json version 0. This file still ships in the
+/// repository, but in the obsolete flat format of the former Python conversion,
+/// which the catalog module (reading the original BNetzA format) cannot read.
+const LEGACY_QUESTIONS_FILEPATH: &str = "./questions/questions.json";
+
+/// Schema of the config.json
+pub const CONFIG_SCHEMA: Schema = Schema {
+    version: CONFIG_VERSION,
+    migrations: &[migrate_config_v0, migrate_config_v1],
+};
+/// Schema of the learning.json
+pub const LEARNING_SCHEMA: Schema = Schema {
+    version: LEARNING_VERSION,
+    migrations: &[migrate_learning_v0],
+};
+
+// STRUCT SECTION //
+/// A function which upgrades a raw JSON value by one version.
+pub type Migration = fn(Value) -> Value;
+
+/// Describes the versioning of a persisted JSON format.
+pub struct Schema {
+    /// The current version
+    pub version: u64,
+    /// The migrations, whereby the migration at index i upgrades
+    /// from version i to version i + 1
+    pub migrations: &'static [Migration],
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Returns the version of the given raw JSON value, i.e. its "version" field,
+/// or 0 if it has none.
+pub fn get_version(value: &Value) -> u64 {
+    value.get("version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Upgrades the given raw JSON value of the given file to the schema's current version.
+///
+/// ### Return value
+/// * The upgraded value together with its original version, or an
+///   FfhError::SchemaVersion if the file's version is newer than the current one
+pub fn migrate(schema: &Schema, mut value: Value, filepath: &str) -> Result<(Value, u64)> {
+    let original_version = get_version(&value);
+    if original_version > schema.version {
+        return Err(FfhError::SchemaVersion {
+            path: filepath.to_string(),
+            found: original_version,
+            supported: schema.version,
+        });
+    }
+    for version in original_version..schema.version {
+        value = schema.migrations[version as usize](value);
+        if let Value::Object(entries) = &mut value {
+            entries.insert(String::from("version"), Value::from(version + 1));
+        }
+    }
+    Ok((value, original_version))
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Upgrades a config.json of version 0. If the questions path is still version 0's
+/// default, i.e. the converted questions JSON in the obsolete format (see
+/// LEGACY_QUESTIONS_FILEPATH), it is replaced with the path of the shipped
+/// catalog. A path which the user set deliberately is kept.
+fn migrate_config_v0(mut value: Value) -> Value {
+    if let Value::Object(entries) = &mut value {
+        let questions_filepath = entries.get("questions_filepath").and_then(Value::as_str);
+        if questions_filepath == Some(LEGACY_QUESTIONS_FILEPATH) {
+            entries.insert(
+                String::from("questions_filepath"),
+                Value::from(DEFAULT_QUESTIONS_FILEPATH),
+            );
+        }
+    }
+    value
+}
+
+/// Upgrades a config.json of version 1, which had the three filter flags
+/// "prefer_marked", "prefer_wrong" and "prefer_new" instead of a filter
+/// expression (see the filter module). The set flags are combined with "or",
+/// as they were before.
+fn migrate_config_v1(mut value: Value) -> Value {
+    if let Value::Object(entries) = &mut value {
+        let mut expressions = Vec::new();
+        let flag_expressions = [
+            ("prefer_marked", json!("Marked")),
+            (
+                "prefer_wrong",
+                json!({"WrongCount": {"comparison": "AtLeast", "value": 1}}),
+            ),
+            ("prefer_new", json!("New")),
+        ];
+        for (flag, expression) in flag_expressions {
+            if entries.remove(flag).and_then(|flag| flag.as_bool()) == Some(true) {
+                expressions.push(expression);
+            }
+        }
+        let filter = if expressions.is_empty() {
+            Value::Null
+        } else {
+            json!({ "Or": expressions })
+        };
+        entries.insert(String::from("filter"), filter);
+    }
+    value
+}
+
+/// Upgrades a learning.json of version 0, which directly contained the
+/// LearnStates, into a versioned object with a "learn_states" field.
+fn migrate_learning_v0(value: Value) -> Value {
+    let mut entries = Map::new();
+    entries.insert(String::from("learn_states"), value);
+    Value::Object(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::{Comparison, FilterExpression};
+
+    #[test]
+    fn files_without_version_are_version_zero() {
+        assert_eq!(get_version(&json!({"include_v": true})), 0);
+        assert_eq!(get_version(&json!({"version": 2})), 2);
+    }
+
+    #[test]
+    fn legacy_questions_filepath_is_replaced() {
+        let value = json!({ "questions_filepath": LEGACY_QUESTIONS_FILEPATH });
+        let (value, original_version) = migrate(&CONFIG_SCHEMA, value, "config.json").unwrap();
+
+        assert_eq!(original_version, 0);
+        assert_eq!(get_version(&value), CONFIG_VERSION);
+        assert_eq!(value["questions_filepath"], DEFAULT_QUESTIONS_FILEPATH);
+    }
+
+    #[test]
+    fn own_questions_filepath_is_kept() {
+        let value = json!({ "questions_filepath": "./eigene/fragen.json" });
+        let (value, _) = migrate(&CONFIG_SCHEMA, value, "config.json").unwrap();
+
+        assert_eq!(value["questions_filepath"], "./eigene/fragen.json");
+    }
+
+    #[test]
+    fn filter_flags_become_a_filter_expression() {
+        let value = json!({
+            "version": 1,
+            "prefer_marked": true,
+            "prefer_wrong": true,
+            "prefer_new": false
+        });
+        let (value, original_version) = migrate(&CONFIG_SCHEMA, value, "config.json").unwrap();
+
+        assert_eq!(original_version, 1);
+        assert!(value.get("prefer_marked").is_none());
+        assert!(value.get("prefer_new").is_none());
+        let filter: FilterExpression = serde_json::from_value(value["filter"].clone()).unwrap();
+        assert_eq!(
+            filter,
+            FilterExpression::Or(vec![
+                FilterExpression::Marked,
+                FilterExpression::WrongCount {
+                    comparison: Comparison::AtLeast,
+                    value: 1
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn no_filter_flags_become_no_filter() {
+        let value = json!({ "version": 1, "prefer_marked": false });
+        let (value, _) = migrate(&CONFIG_SCHEMA, value, "config.json").unwrap();
+
+        assert_eq!(value["filter"], Value::Null);
+    }
+
+    #[test]
+    fn learn_states_are_wrapped_into_a_versioned_object() {
+        let learn_states = json!({ "NA101": { "current_bin": 2 } });
+        let (value, original_version) =
+            migrate(&LEARNING_SCHEMA, learn_states.clone(), "learning.json").unwrap();
+
+        assert_eq!(original_version, 0);
+        assert_eq!(value, json!({ "version": 1, "learn_states": learn_states }));
+    }
+
+    #[test]
+    fn current_file_is_unchanged() {
+        let value = json!({ "version": LEARNING_VERSION, "learn_states": {} });
+        let (migrated_value, original_version) =
+            migrate(&LEARNING_SCHEMA, value.clone(), "learning.json").unwrap();
+
+        assert_eq!(original_version, LEARNING_VERSION);
+        assert_eq!(migrated_value, value);
+    }
+
+    #[test]
+    fn newer_file_is_rejected() {
+        let value = json!({ "version": CONFIG_VERSION + 1 });
+        let result = migrate(&CONFIG_SCHEMA, value, "config.json");
+
+        assert!(matches!(
+            result,
+            Err(FfhError::SchemaVersion { found, supported, .. })
+                if found == CONFIG_VERSION + 1 && supported == CONFIG_VERSION
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+//! This module determines where Funkfragenhelfer's files are located.
+//!
+//! Two kinds of files are distinguished:
+//! * The user's data (the Config, the LearnStates and the answer history),
+//!   which is stored in a "data dir". By default, this is the platform's usual
+//!   directory for application data (e.g. "~/.local/share/funkfragenhelfer"
+//!   under Linux, following the XDG base directory specification). It can be
+//!   overridden with the command line argument "--data-dir".
+//! * The resources shipped with Funkfragenhelfer (the question catalog and its
+//!   pictures), which are looked up relative to the current working directory,
+//!   the executable's directory and (only in debug builds, e.g. with "cargo
+//!   run") the source directory, in this order.
+//!
+//! For compatibility with older versions, which always stored their data in
+//! the current working directory, the current working directory is used as
+//! data dir if it already contains a "config/config.json".
+
+// IMPORTS SECTION //
+use std::env;
+use std::path::{Path, PathBuf};
+
+// CONSTANTS SECTION //
+/// Name of Funkfragenhelfer's directory inside the platform's data directory
+const APP_DIR_NAME: &str = "funkfragenhelfer";
+/// Path of the config file, relative to the data dir
+pub const CONFIG_FILEPATH: &str = "config/config.json";
+
+// PUBLIC FUNCTION SECTION //
+/// Returns the data dir, i.e. the given override (from "--data-dir") if any,
+/// the current working directory if it contains the data of an older version,
+/// or the platform's default data directory otherwise.
+///
+/// The data dir does not need to exist: Its subdirectories are created when
+/// the Config and the LearnStates are loaded (see the config and learning
+/// modules), which also creates an overriding data dir that does not exist yet.
+pub fn get_data_dir(data_dir_override: Option<&str>) -> String {
+    if let Some(data_dir) = data_dir_override {
+        return data_dir.to_string();
+    }
+    if Path::new(".").join(CONFIG_FILEPATH).exists() {
+        return String::from(".");
+    }
+    match get_platform_data_dir() {
+        Some(platform_data_dir) => path_to_string(&platform_data_dir.join(APP_DIR_NAME)),
+        None => String::from("."),
+    }
+}
+
+/// Resolves the given path of a user data file against the given data dir. Absolute
+/// paths are returned unchanged.
+pub fn resolve_data_path(data_dir: &str, filepath: &str) -> String {
+    let path = Path::new(filepath);
+    if path.is_absolute() {
+        return filepath.to_string();
+    }
+    path_to_string(&Path::new(data_dir).join(path))
+}
+
+/// Resolves the given path of a shipped resource (such as the question catalog).
+///
+/// Absolute paths are returned unchanged. Relative paths are looked up relative to
+/// the current working directory, the executable's directory and, in debug builds,
+/// the directory of Funkfragenhelfer's sources, whereby the first existing file is
+/// used. If the resource exists nowhere, the path is returned unchanged. Release
+/// builds do not look into the sources, so that the build machine's source path
+/// is not part of the executable.
+pub fn resolve_resource_path(filepath: &str) -> String {
+    let path = Path::new(filepath);
+    if path.is_absolute() || path.exists() {
+        return filepath.to_string();
+    }
+    let mut base_dirs = Vec::new();
+    if let Some(exe_dir) = env::current_exe()
+        .ok()
+        .and_then(|exe_path| exe_path.parent().map(Path::to_path_buf))
+    {
+        base_dirs.push(exe_dir);
+    }
+    #[cfg(debug_assertions)]
+    base_dirs.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+    for base_dir in base_dirs {
+        let candidate = base_dir.join(path);
+        if candidate.exists() {
+            return path_to_string(&candidate);
+        }
+    }
+    filepath.to_string()
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Returns the platform's directory for application data, i.e. %APPDATA% under
+/// Windows, "~/Library/Application Support" under macOS and $XDG_DATA_HOME (or
+/// "~/.local/share") otherwise, or None if it cannot be determined.
+fn get_platform_data_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        return env::var_os("APPDATA").map(PathBuf::from);
+    }
+    let home_dir = env::var_os("HOME")
+        .map(PathBuf::from)
+        .filter(|home_dir| home_dir.is_absolute());
+    if cfg!(target_os = "macos") {
+        return home_dir.map(|home_dir| home_dir.join("Library/Application Support"));
+    }
+    // Following the XDG base directory specification, relative paths are ignored
+    let xdg_data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|xdg_data_home| xdg_data_home.is_absolute());
+    xdg_data_home.or_else(|| home_dir.map(|home_dir| home_dir.join(".local/share")))
+}
+
+/// Converts the given path into a String with "/" as separator (as used
+/// throughout Funkfragenhelfer).
+fn path_to_string(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_dir_override_is_used_as_given() {
+        assert_eq!(
+            get_data_dir(Some("/does/not/exist/yet")),
+            "/does/not/exist/yet"
+        );
+    }
+
+    #[test]
+    fn data_paths_are_resolved_against_the_data_dir() {
+        assert_eq!(
+            resolve_data_path("/data", "config/config.json"),
+            "/data/config/config.json"
+        );
+        let absolute_path = path_to_string(&env::temp_dir().join("learning.json"));
+        assert_eq!(resolve_data_path("/data", &absolute_path), absolute_path);
+    }
+
+    #[test]
+    fn missing_resources_are_returned_unchanged() {
+        assert_eq!(
+            resolve_resource_path("does/not/exist.json"),
+            "does/not/exist.json"
+        );
+    }
+}
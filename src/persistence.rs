@@ -0,0 +1,476 @@
+//! This module contains the crash-safe persistence of Funkfragenhelfer's
+//! JSON files (i.e., "config.json" and "learning.json").
+//!
+//! Files are never overwritten in place. Instead, the new content is written
+//! into a temporary file next to the target, which then replaces the target
+//! by renaming it. As renaming is atomic, the target is always either the old
+//! or the new version, even if the program crashes or the power is lost.
+//!
+//! In addition, each time a file is loaded successfully, a copy is kept as
+//! backup ("<file>.bak1" is the newest, up to "<file>.bak3" the oldest). If the
+//! file is no valid JSON on load (e.g. because it was only partially written),
+//! the newest readable backup is restored and a warning is returned, which is
+//! shown to the user. If the file is valid JSON but does not fit (e.g. because
+//! a field is missing after a manual edit), or if no backup is readable, an
+//! FfhError::Parse is returned instead, so that the user can decide whether
+//! the file is repaired (see repair_json()) or reset (see reset_json()).
+//!
+//! Files of an older schema version are upgraded on load (see the migration
+//! module), whereby the original file is kept as "<file>.v<version>".
+
+// IMPORTS SECTION //
+use crate::error::{FfhError, Result};
+use crate::migration::{self, Schema};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+// CONSTANTS SECTION //
+/// Number of kept backups per file
+pub const BACKUP_COUNT: usize = 3;
+
+// STRUCT SECTION //
+/// The result of loading a JSON file.
+pub struct Loaded<T> {
+    /// The loaded (or restored) value
+    pub value: T,
+    /// A warning for the user if the file could not be read and a backup
+    /// was used instead
+    pub warning: Option<String>,
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Atomically replaces the content of the given file with the given text.
+///
+/// The text is written into "<file>.tmp" and flushed to disk first,
+/// then the temporary file is renamed to the given file.
+pub fn write_atomically(filepath: &str, text: &str) -> Result<()> {
+    let temp_filepath = format!("{}.tmp", filepath);
+    fs::File::create(&temp_filepath)
+        .and_then(|mut f| {
+            writeln!(f, "{}", text)?;
+            f.sync_all()
+        })
+        .map_err(|e| FfhError::io(&temp_filepath, e))?;
+    fs::rename(&temp_filepath, filepath).map_err(|e| FfhError::io(filepath, e))
+}
+
+/// Atomically saves the given value as pretty JSON into the given file.
+pub fn save_json<T: Serialize>(filepath: &str, value: &T) -> Result<()> {
+    let json_str = serde_json::to_string_pretty(value).map_err(|e| FfhError::parse(filepath, e))?;
+    write_atomically(filepath, &json_str)
+}
+
+/// Returns the path of the given file's backup with the given number
+/// (1 is the newest backup).
+pub fn get_backup_filepath(filepath: &str, number: usize) -> String {
+    format!("{}.bak{}", filepath, number)
+}
+
+/// Reads the newest readable backup of the given JSON file (upgraded to the
+/// schema's current version), e.g. to take over content which cannot be
+/// recovered otherwise.
+///
+/// ### Return value
+/// * The backup's value together with the backup's path, or None if no backup
+///   is readable
+pub fn read_newest_backup<T: DeserializeOwned>(
+    filepath: &str,
+    schema: &Schema,
+) -> Option<(T, String)> {
+    (1..=BACKUP_COUNT).find_map(|number| {
+        let backup_filepath = get_backup_filepath(filepath, number);
+        let value = read_json::<T>(&backup_filepath, schema)?;
+        Some((value, backup_filepath))
+    })
+}
+
+/// Loads the given JSON file, recovering from a partially written file if possible.
+///
+/// * If the file does not exist, it is created with the default value.
+/// * If the file can be read, it is upgraded to the schema's current version (if
+///   necessary) and a backup of it is made (see rotate_backups()).
+/// * If the file is no valid JSON, the newest readable backup is restored, whereby
+///   the unreadable file is kept as "<file>.corrupt" and a warning is returned.
+///
+/// ### Arguments
+/// * filepath: The JSON file's path
+/// * schema: The schema of the file (see the migration module)
+/// * get_default: Returns the default value for a missing file
+///
+/// ### Return value
+/// * The loaded value, or an FfhError::Parse if the file does not fit to the
+///   value's type and could not be restored from a backup, an FfhError::SchemaVersion
+///   if the file is of a newer schema version, or an FfhError::Io
+pub fn load_json<T, F>(filepath: &str, schema: &Schema, get_default: F) -> Result<Loaded<T>>
+where
+    T: Serialize + DeserializeOwned,
+    F: Fn() -> T,
+{
+    if !Path::new(filepath).exists() {
+        let value = get_default();
+        save_json(filepath, &value)?;
+        return Ok(Loaded {
+            value,
+            warning: None,
+        });
+    }
+
+    let json_str = fs::read_to_string(filepath).map_err(|e| FfhError::io(filepath, e))?;
+    let json_value: Value = match serde_json::from_str(&json_str) {
+        Ok(json_value) => json_value,
+        // The file is no valid JSON (e.g. partially written): Look for a readable backup
+        Err(e) => {
+            return restore_newest_backup(filepath, schema)
+                .ok_or_else(|| FfhError::parse(filepath, e))
+        }
+    };
+    let (json_value, original_version) = migration::migrate(schema, json_value, filepath)?;
+    if original_version < schema.version {
+        // Keep the original file before it is upgraded in place
+        let version_filepath = format!("{}.v{}", filepath, original_version);
+        fs::copy(filepath, &version_filepath).map_err(|e| FfhError::io(&version_filepath, e))?;
+        save_json(filepath, &json_value)?;
+    }
+    let value = serde_json::from_value(json_value).map_err(|e| FfhError::parse(filepath, e))?;
+    rotate_backups(filepath);
+    Ok(Loaded {
+        value,
+        warning: None,
+    })
+}
+
+/// Repairs the given JSON file with the given repair function, which gets the file's
+/// content (upgraded to the schema's current version) and returns the repaired value.
+/// The original file is kept as "<file>.corrupt".
+///
+/// ### Return value
+/// * The repaired value, or an FfhError if the file is no valid JSON at all or
+///   cannot be repaired
+pub fn repair_json<T, R>(filepath: &str, schema: &Schema, repair: R) -> Result<T>
+where
+    T: Serialize,
+    R: Fn(Value) -> Option<T>,
+{
+    let json_str = fs::read_to_string(filepath).map_err(|e| FfhError::io(filepath, e))?;
+    let file_value: Value =
+        serde_json::from_str(&json_str).map_err(|e| FfhError::parse(filepath, e))?;
+    let (file_value, _) = migration::migrate(schema, file_value, filepath)?;
+    let value: T = repair(file_value)
+        .ok_or_else(|| FfhError::parse(filepath, "Die Datei passt nicht zum erwarteten Format"))?;
+    keep_corrupt_file(filepath)?;
+    save_json(filepath, &value)?;
+    Ok(value)
+}
+
+/// Repairs the given JSON value of a struct by replacing all of its missing or
+/// unreadable fields with the fields of the given default, while keeping all
+/// readable fields. Used as repair function for repair_json().
+///
+/// Starting with the default value, each field of the given value is taken over.
+/// If all fields together cannot be read as T, the fields are taken over one by
+/// one, skipping each field which makes the value unreadable.
+///
+/// ### Return value
+/// * The repaired struct, or None if the given value is no JSON object
+pub fn repair_struct<T: Serialize + DeserializeOwned>(default: &T, value: Value) -> Option<T> {
+    let Value::Object(mut repaired) = serde_json::to_value(default).ok()? else {
+        return None;
+    };
+    let Value::Object(entries) = value else {
+        return None;
+    };
+
+    // Fast path: Only some fields were missing
+    let mut merged = repaired.clone();
+    merged.extend(entries.clone());
+    if let Ok(value) = serde_json::from_value(Value::Object(merged)) {
+        return Some(value);
+    }
+
+    // Slow path: Take over the readable fields one by one
+    for (key, entry) in entries {
+        let previous_entry = repaired.insert(key.clone(), entry);
+        if serde_json::from_value::<T>(Value::Object(repaired.clone())).is_err() {
+            match previous_entry {
+                Some(previous_entry) => repaired.insert(key, previous_entry),
+                None => repaired.remove(&key),
+            };
+        }
+    }
+    serde_json::from_value(Value::Object(repaired)).ok()
+}
+
+/// Resets the given JSON file to the default value. The original file is kept as
+/// "<file>.corrupt" (if it exists).
+pub fn reset_json<T, F>(filepath: &str, get_default: F) -> Result<T>
+where
+    T: Serialize,
+    F: Fn() -> T,
+{
+    if Path::new(filepath).exists() {
+        keep_corrupt_file(filepath)?;
+    }
+    let value = get_default();
+    save_json(filepath, &value)?;
+    Ok(value)
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Reads and parses the given JSON file (upgraded to the schema's current version),
+/// or returns None if the file does not exist or cannot be parsed.
+fn read_json<T: DeserializeOwned>(filepath: &str, schema: &Schema) -> Option<T> {
+    let json_str = fs::read_to_string(filepath).ok()?;
+    let value: Value = serde_json::from_str(&json_str).ok()?;
+    let (value, _) = migration::migrate(schema, value, filepath).ok()?;
+    serde_json::from_value(value).ok()
+}
+
+/// Renames the given file to "<file>.corrupt", so that it is kept for inspection.
+fn keep_corrupt_file(filepath: &str) -> Result<String> {
+    let corrupt_filepath = format!("{}.corrupt", filepath);
+    fs::rename(filepath, &corrupt_filepath).map_err(|e| FfhError::io(filepath, e))?;
+    Ok(corrupt_filepath)
+}
+
+/// Replaces the given unreadable file with its newest readable backup.
+///
+/// ### Return value
+/// * The restored value together with a warning for the user, or None if no
+///   backup is readable (in which case the file is left unchanged)
+fn restore_newest_backup<T: Serialize + DeserializeOwned>(
+    filepath: &str,
+    schema: &Schema,
+) -> Option<Loaded<T>> {
+    let (value, backup_filepath) = read_newest_backup::<T>(filepath, schema)?;
+    let corrupt_filepath = keep_corrupt_file(filepath).ok()?;
+    save_json(filepath, &value).ok()?;
+    Some(Loaded {
+        value,
+        warning: Some(format!(
+            "Die Datei '{}' war beschädigt und wurde aus der Sicherung '{}' \
+             wiederhergestellt. Die beschädigte Datei wurde als '{}' aufbewahrt.",
+            filepath, backup_filepath, corrupt_filepath
+        )),
+    })
+}
+
+/// Copies the given (readable) file to its newest backup, whereby the older
+/// backups are shifted by one and the oldest backup is dropped. As backups
+/// are only a safety net, errors are ignored.
+fn rotate_backups(filepath: &str) {
+    for number in (1..BACKUP_COUNT).rev() {
+        let backup_filepath = get_backup_filepath(filepath, number);
+        if Path::new(&backup_filepath).exists() {
+            let _ = fs::rename(&backup_filepath, get_backup_filepath(filepath, number + 1));
+        }
+    }
+    let newest_backup_filepath = get_backup_filepath(filepath, 1);
+    if let Ok(text) = fs::read_to_string(filepath) {
+        let _ = write_atomically(&newest_backup_filepath, text.trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    /// The content of a test file.
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct TestFile {
+        version: u64,
+        name: String,
+        count: u64,
+    }
+
+    /// Schema of a test file, whose version 0 had no name.
+    const TEST_SCHEMA: Schema = Schema {
+        version: 1,
+        migrations: &[migrate_test_v0],
+    };
+
+    /// Upgrades a test file of version 0 by adding the name.
+    fn migrate_test_v0(mut value: Value) -> Value {
+        if let Value::Object(entries) = &mut value {
+            entries.insert(String::from("name"), Value::from("alt"));
+        }
+        value
+    }
+
+    /// Returns the default content of a test file.
+    fn get_default() -> TestFile {
+        TestFile {
+            version: 1,
+            name: String::from("neu"),
+            count: 0,
+        }
+    }
+
+    /// Returns the path of a test file in a new, empty temporary directory
+    /// with the given name.
+    fn get_temp_filepath(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!(
+            "ffh-persistence-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("test.json").to_string_lossy().to_string()
+    }
+
+    /// Loads the given test file.
+    fn load(filepath: &str) -> Result<Loaded<TestFile>> {
+        load_json(filepath, &TEST_SCHEMA, get_default)
+    }
+
+    #[test]
+    fn missing_file_is_created_with_the_default() {
+        let filepath = get_temp_filepath("missing");
+        let loaded = load(&filepath).unwrap();
+
+        assert_eq!(loaded.value, get_default());
+        assert!(loaded.warning.is_none());
+        assert!(Path::new(&filepath).exists());
+    }
+
+    #[test]
+    fn saved_file_is_loaded_again_without_a_temporary_file() {
+        let filepath = get_temp_filepath("saved");
+        let value = TestFile {
+            count: 7,
+            ..get_default()
+        };
+        save_json(&filepath, &value).unwrap();
+
+        assert!(!Path::new(&format!("{}.tmp", filepath)).exists());
+        assert_eq!(load(&filepath).unwrap().value, value);
+    }
+
+    #[test]
+    fn each_load_rotates_the_backups() {
+        let filepath = get_temp_filepath("rotated");
+        for count in 0..=BACKUP_COUNT as u64 + 1 {
+            let value = TestFile {
+                count,
+                ..get_default()
+            };
+            save_json(&filepath, &value).unwrap();
+            load(&filepath).unwrap();
+        }
+
+        // The newest backup is the last loaded file, the oldest backup is dropped
+        let (newest_backup, backup_filepath) =
+            read_newest_backup::<TestFile>(&filepath, &TEST_SCHEMA).unwrap();
+        assert_eq!(newest_backup.count, BACKUP_COUNT as u64 + 1);
+        assert_eq!(backup_filepath, get_backup_filepath(&filepath, 1));
+        assert!(Path::new(&get_backup_filepath(&filepath, BACKUP_COUNT)).exists());
+        assert!(!Path::new(&get_backup_filepath(&filepath, BACKUP_COUNT + 1)).exists());
+    }
+
+    #[test]
+    fn partially_written_file_is_restored_from_the_newest_backup() {
+        let filepath = get_temp_filepath("partial");
+        let value = TestFile {
+            count: 3,
+            ..get_default()
+        };
+        save_json(&filepath, &value).unwrap();
+        load(&filepath).unwrap();
+        // A crash while writing left only the beginning of the file
+        fs::write(&filepath, "{\"version\": 1, \"na").unwrap();
+
+        let loaded = load(&filepath).unwrap();
+        assert_eq!(loaded.value, value);
+        assert!(loaded.warning.is_some());
+        assert!(Path::new(&format!("{}.corrupt", filepath)).exists());
+        // The restored file is readable again
+        assert!(load(&filepath).unwrap().warning.is_none());
+    }
+
+    #[test]
+    fn unreadable_file_without_backup_is_a_parse_error() {
+        let filepath = get_temp_filepath("unreadable");
+        fs::write(&filepath, "{").unwrap();
+
+        assert!(matches!(load(&filepath), Err(FfhError::Parse { .. })));
+        // The file is left unchanged for the user's decision
+        assert_eq!(fs::read_to_string(&filepath).unwrap(), "{");
+    }
+
+    #[test]
+    fn older_file_is_upgraded_and_kept() {
+        let filepath = get_temp_filepath("older");
+        fs::write(&filepath, "{\"count\": 5}").unwrap();
+
+        let loaded = load(&filepath).unwrap();
+        assert_eq!(
+            loaded.value,
+            TestFile {
+                version: 1,
+                name: String::from("alt"),
+                count: 5,
+            }
+        );
+        assert_eq!(
+            fs::read_to_string(format!("{}.v0", filepath)).unwrap(),
+            "{\"count\": 5}"
+        );
+    }
+
+    #[test]
+    fn newer_file_is_a_schema_version_error() {
+        let filepath = get_temp_filepath("newer");
+        fs::write(&filepath, "{\"version\": 2, \"name\": \"x\", \"count\": 1}").unwrap();
+
+        assert!(matches!(
+            load(&filepath),
+            Err(FfhError::SchemaVersion {
+                found: 2,
+                supported: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn repair_keeps_the_readable_fields() {
+        let repaired: TestFile =
+            repair_struct(&get_default(), json!({"name": "Test", "count": "viele"})).unwrap();
+        assert_eq!(
+            repaired,
+            TestFile {
+                name: String::from("Test"),
+                ..get_default()
+            }
+        );
+        assert!(repair_struct(&get_default(), json!([1, 2])).is_none());
+    }
+
+    #[test]
+    fn repaired_and_reset_files_keep_the_original() {
+        let filepath = get_temp_filepath("repaired");
+        fs::write(&filepath, "{\"version\": 1, \"count\": 2}").unwrap();
+
+        let repaired: TestFile = repair_json(&filepath, &TEST_SCHEMA, |value| {
+            repair_struct(&get_default(), value)
+        })
+        .unwrap();
+        assert_eq!(repaired.count, 2);
+        assert_eq!(load(&filepath).unwrap().value, repaired);
+
+        let reset = reset_json(&filepath, get_default).unwrap();
+        assert_eq!(reset, get_default());
+        assert_eq!(
+            fs::read_to_string(format!("{}.corrupt", filepath))
+                .unwrap()
+                .trim(),
+            serde_json::to_string_pretty(&repaired).unwrap()
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! This module resolves the picture identifiers of the questions
+//! (e.g. "NF103_q") to the actual picture files.
+//!
+//! The catalog only gives picture identifiers without file extension,
+//! and the pictures are not all of the same format (most are SVGs,
+//! some are (also) PNGs). Hence, the pictures directory is scanned once and each
+//! picture identifier is mapped to its file, regardless of the extension.
+
+// IMPORTS SECTION //
+use crate::helper;
+use crate::question::Question;
+use std::collections::HashMap;
+use std::path::Path;
+
+// CONSTANTS SECTION //
+/// The supported picture file extensions, in the order of preference
+/// (i.e., if a picture exists as SVG and PNG, the SVG is used)
+const PICTURE_EXTENSIONS: [&str; 6] = ["svg", "png", "jpg", "jpeg", "gif", "bmp"];
+
+// STRUCT SECTION //
+/// A question picture which is referenced by a question but whose
+/// file could not be found.
+#[derive(Debug)]
+pub struct MissingPicture {
+    /// The identifier of the question which references the picture
+    pub question_identifier: String,
+    /// The identifier of the missing picture
+    pub picture: String,
+}
+
+/// Index of all picture files, with the picture identifiers
+/// (i.e., the file names without extension) as keys and the
+/// file paths as values.
+pub struct PictureIndex {
+    paths: HashMap<String, String>,
+}
+
+impl PictureIndex {
+    /// Creates a new PictureIndex by scanning the given directory.
+    /// If the directory does not exist or cannot be read, the index is empty.
+    pub fn new(dir: &str) -> PictureIndex {
+        // Picture identifiers with the preference rank and path of their best file
+        let mut preferred_files: HashMap<String, (usize, String)> = HashMap::new();
+        if Path::new(dir).is_dir() {
+            for filepath in helper::get_filenames_in_dir(dir).unwrap_or_default() {
+                let path = Path::new(&filepath);
+                let (Some(stem), Some(extension)) = (path.file_stem(), path.extension()) else {
+                    continue;
+                };
+                let extension = extension.to_string_lossy().to_lowercase();
+                let Some(preference) = get_extension_preference(&extension) else {
+                    continue;
+                };
+                let picture = stem.to_string_lossy().to_string();
+                let is_preferred = match preferred_files.get(&picture) {
+                    Some((known_preference, _)) => preference < *known_preference,
+                    None => true,
+                };
+                if is_preferred {
+                    preferred_files.insert(picture, (preference, filepath));
+                }
+            }
+        }
+        let paths = preferred_files
+            .into_iter()
+            .map(|(picture, (_, filepath))| (picture, filepath))
+            .collect();
+        PictureIndex { paths }
+    }
+
+    /// Returns the path of the file of the given picture identifier,
+    /// or None if no such file exists.
+    pub fn get_path(&self, picture: &str) -> Option<&str> {
+        self.paths.get(picture).map(String::as_str)
+    }
+
+    /// Returns the URI (as used by image loaders) of the file of the
+    /// given picture identifier, or None if no such file exists.
+    pub fn get_uri(&self, picture: &str) -> Option<String> {
+        self.get_path(picture)
+            .map(|path| format!("file://{}", path.trim_start_matches("./")))
+    }
+
+    /// Returns all pictures of the given questions (question pictures as well as
+    /// answer pictures) for which no file exists.
+    pub fn get_missing_pictures(&self, questions: &[Question]) -> Vec<MissingPicture> {
+        let mut missing_pictures = Vec::new();
+        for question in questions {
+            let pictures = [
+                &question.picture_question,
+                &question.picture_a,
+                &question.picture_b,
+                &question.picture_c,
+                &question.picture_d,
+            ];
+            for picture in pictures {
+                if !picture.is_empty() && self.get_path(picture).is_none() {
+                    missing_pictures.push(MissingPicture {
+                        question_identifier: question.identifier.clone(),
+                        picture: picture.clone(),
+                    });
+                }
+            }
+        }
+        missing_pictures
+    }
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Returns the preference rank of the given (lowercase) file extension (lower is
+/// better), or None if it is no supported picture format.
+fn get_extension_preference(extension: &str) -> Option<usize> {
+    PICTURE_EXTENSIONS
+        .iter()
+        .position(|known_extension| *known_extension == extension)
+}
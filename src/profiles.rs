@@ -0,0 +1,122 @@
+//! This module manages the learner profiles, so that several people can
+//! learn with the same Funkfragenhelfer installation.
+//!
+//! Each profile has its own Config and LearnStates, which are stored in the
+//! profile's own data dir (see the paths module). The default profile
+//! ("Standard") uses the data dir itself, so that the data of older versions
+//! becomes the default profile. All other profiles are stored in
+//! "<data dir>/profiles/<profile name>".
+
+// IMPORTS SECTION //
+use crate::error::{FfhError, Result};
+use crate::paths;
+use std::fs;
+use std::path::Path;
+
+// CONSTANTS SECTION //
+/// Name of the default profile, which uses the data dir itself
+pub const DEFAULT_PROFILE_NAME: &str = "Standard";
+/// Directory (relative to the data dir) which contains all other profiles
+const PROFILES_DIR: &str = "profiles";
+/// Characters which are not allowed in profile names, as the names are
+/// used as directory names
+const FORBIDDEN_CHARACTERS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+// PUBLIC FUNCTION SECTION //
+/// Returns the names of all profiles of the given data dir, with the default
+/// profile first and all other profiles in alphabetical order.
+pub fn get_profile_names(data_dir: &str) -> Result<Vec<String>> {
+    let mut profile_names = Vec::new();
+    let profiles_dir = paths::resolve_data_path(data_dir, PROFILES_DIR);
+    if Path::new(&profiles_dir).is_dir() {
+        let entries = fs::read_dir(&profiles_dir).map_err(|e| FfhError::io(&profiles_dir, e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| FfhError::io(&profiles_dir, e))?;
+            if entry.path().is_dir() {
+                profile_names.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    profile_names.sort();
+    profile_names.insert(0, String::from(DEFAULT_PROFILE_NAME));
+    Ok(profile_names)
+}
+
+/// Returns the data dir of the profile with the given name, in which its
+/// Config and LearnStates are stored.
+pub fn get_profile_dir(data_dir: &str, profile_name: &str) -> String {
+    if profile_name == DEFAULT_PROFILE_NAME {
+        return data_dir.to_string();
+    }
+    let profiles_dir = paths::resolve_data_path(data_dir, PROFILES_DIR);
+    paths::resolve_data_path(&profiles_dir, profile_name)
+}
+
+/// Checks whether the given name can be used for a new profile of the given
+/// data dir.
+///
+/// ### Return value
+/// * Nothing, or an error message for the user if the name is empty, contains
+///   a forbidden character or is already used
+pub fn check_new_profile_name(
+    data_dir: &str,
+    profile_name: &str,
+) -> std::result::Result<(), String> {
+    if profile_name.trim().is_empty() {
+        return Err(String::from("Der Profilname darf nicht leer sein."));
+    }
+    if profile_name != profile_name.trim()
+        || profile_name.starts_with('.')
+        || profile_name.ends_with('.')
+    {
+        return Err(String::from(
+            "Der Profilname darf nicht mit einem Leerzeichen oder Punkt beginnen oder enden.",
+        ));
+    }
+    if profile_name
+        .chars()
+        .any(|c| FORBIDDEN_CHARACTERS.contains(&c) || c.is_control())
+    {
+        return Err(format!(
+            "Der Profilname darf keines der Zeichen {} enthalten.",
+            FORBIDDEN_CHARACTERS.iter().collect::<String>()
+        ));
+    }
+    let profile_names = get_profile_names(data_dir).map_err(|e| e.to_string())?;
+    if profile_names
+        .iter()
+        .any(|existing_name| existing_name.to_lowercase() == profile_name.to_lowercase())
+    {
+        return Err(format!("Das Profil '{}' existiert bereits.", profile_name));
+    }
+    Ok(())
+}
+
+/// Creates a new, empty profile with the given name. Its Config and LearnStates
+/// are created with default values when the profile is loaded for the first time.
+/// Make sure beforehand that the name is valid (see check_new_profile_name()).
+pub fn create_profile(data_dir: &str, profile_name: &str) -> Result<()> {
+    let profile_dir = get_profile_dir(data_dir, profile_name);
+    fs::create_dir_all(&profile_dir).map_err(|e| FfhError::io(&profile_dir, e))
+}
+
+/// Renames the given profile. The default profile cannot be renamed. Make sure
+/// beforehand that the new name is valid (see check_new_profile_name()).
+pub fn rename_profile(data_dir: &str, profile_name: &str, new_profile_name: &str) -> Result<()> {
+    if profile_name == DEFAULT_PROFILE_NAME {
+        return Ok(());
+    }
+    let profile_dir = get_profile_dir(data_dir, profile_name);
+    let new_profile_dir = get_profile_dir(data_dir, new_profile_name);
+    fs::rename(&profile_dir, &new_profile_dir).map_err(|e| FfhError::io(&profile_dir, e))
+}
+
+/// Deletes the given profile together with its Config and LearnStates. The
+/// default profile cannot be deleted.
+pub fn delete_profile(data_dir: &str, profile_name: &str) -> Result<()> {
+    if profile_name == DEFAULT_PROFILE_NAME {
+        return Ok(());
+    }
+    let profile_dir = get_profile_dir(data_dir, profile_name);
+    fs::remove_dir_all(&profile_dir).map_err(|e| FfhError::io(&profile_dir, e))
+}
@@ -4,7 +4,10 @@
 
 // IMPORTS SECTION //
 use crate::config::Config;
+use crate::deck;
+use crate::learning::LearnStates;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 // ENUM SECTION //
 /// Representation of main questions categories, i.e.:
@@ -13,7 +16,7 @@ use serde::{Deserialize, Serialize};
 /// * N - Technik Klasse N
 /// * E - Technik Klasse E
 /// * A - Technik Klasse A
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
 pub enum Category {
     V,
     B,
@@ -22,15 +25,46 @@ pub enum Category {
     A,
 }
 
+impl Category {
+    /// All categories in the order of the catalog
+    pub const ALL: [Category; 5] = [
+        Category::V,
+        Category::B,
+        Category::N,
+        Category::E,
+        Category::A,
+    ];
+
+    /// Returns the category of the question with the given identifier,
+    /// which is the identifier's first letter (e.g. "N" for "NA103").
+    /// If the letter is no known category, None is returned.
+    pub fn from_identifier(identifier: &str) -> Option<Category> {
+        match identifier.chars().next() {
+            Some('V') => Some(Category::V),
+            Some('B') => Some(Category::B),
+            Some('N') => Some(Category::N),
+            Some('E') => Some(Category::E),
+            Some('A') => Some(Category::A),
+            _ => None,
+        }
+    }
+}
+
 // STRUCT SECTION //
 /// Representation of the full data for a question, including
-/// its question category, question and answer texts.
+/// its question category, catalog sections, question and answer texts.
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Question {
     /// The question's main category
     pub category: Category,
     /// The question's identifier or "number"
     pub identifier: String,
+    /// The titles of all catalog sections which contain the question,
+    /// starting with the outermost section (e.g. ["Prüfungsfragen im
+    /// Prüfungsteil: Technische Kenntnisse", "Allgemeine mathematische
+    /// Grundkenntnisse und Größen", "Allgemeine mathematische Grundkenntnisse"])
+    #[serde(default)]
+    pub section_path: Vec<String>,
     /// The actual question text
     pub question: String,
 
@@ -58,10 +92,22 @@ pub struct Question {
 // PUBLIC FUNCTION SECTION //
 /// Out of the given set of questions, a new set of questions is returned
 /// which includes only the questions that are a part of the config-allowed
-/// categories.
-pub fn get_eligible_questions(questions: &Vec<Question>, config: &Config) -> Vec<Question> {
+/// categories and sections, or, if the Config has an active study deck, only
+/// the questions of this deck (see the deck module). The LearnStates provide
+/// the tags and bins which a deck can depend on.
+pub fn get_eligible_questions(
+    questions: &[Question],
+    config: &Config,
+    learn_states: &LearnStates,
+) -> Vec<Question> {
+    if let Some(deck) = config.get_active_deck() {
+        return deck::get_deck_questions(questions, deck, learn_states);
+    }
     let mut eligible_questions = Vec::new();
     for question in questions {
+        if !config.is_section_included(&question.section_path) {
+            continue;
+        }
         match question.category {
             Category::V => {
                 if config.include_v {
@@ -92,3 +138,126 @@ pub fn get_eligible_questions(questions: &Vec<Question>, config: &Config) -> Vec
     }
     eligible_questions
 }
+
+/// Returns all section paths that occur in the given questions, including
+/// the paths of all parent sections, in the order of their first occurrence.
+/// Hence, each section directly follows its parent section, so that the
+/// result can be shown as a section tree.
+pub fn get_section_paths(questions: &[Question]) -> Vec<Vec<String>> {
+    let mut section_paths = Vec::new();
+    let mut known_section_paths = HashSet::new();
+    for question in questions {
+        for depth in 1..=question.section_path.len() {
+            let section_path = question.section_path[..depth].to_vec();
+            if known_section_paths.insert(section_path.clone()) {
+                section_paths.push(section_path);
+            }
+        }
+    }
+    section_paths
+}
+
+/// Returns a question with the given identifier (of which the first letter
+/// determines the category) in the given section, whose question text is
+/// "Frage <identifier>" and whose answers are "A" to "D", for the tests of all
+/// modules.
+#[cfg(test)]
+pub(crate) fn test_question(identifier: &str, section_path: &[&str]) -> Question {
+    Question {
+        category: Category::from_identifier(identifier).unwrap(),
+        identifier: identifier.to_string(),
+        section_path: section_path
+            .iter()
+            .map(|section| section.to_string())
+            .collect(),
+        question: format!("Frage {}", identifier),
+        answer_a: String::from("A"),
+        answer_b: String::from("B"),
+        answer_c: String::from("C"),
+        answer_d: String::from("D"),
+        picture_question: String::new(),
+        picture_a: String::new(),
+        picture_b: String::new(),
+        picture_c: String::new(),
+        picture_d: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+
+    /// Returns two questions of each category.
+    fn get_questions() -> Vec<Question> {
+        [
+            "VA101", "VA102", "BA101", "BA102", "NA101", "NA102", "EA101", "EA102", "AA101",
+            "AA102",
+        ]
+        .iter()
+        .map(|identifier| test_question(identifier, &[&identifier[..1], identifier]))
+        .collect()
+    }
+
+    /// Returns the default Config with only the given category included.
+    fn get_config_for(category: Category) -> Config {
+        let mut config = Config::new();
+        config.include_v = category == Category::V;
+        config.include_b = category == Category::B;
+        config.include_n = category == Category::N;
+        config.include_e = category == Category::E;
+        config.include_a = category == Category::A;
+        config
+    }
+
+    #[test]
+    fn eligible_questions_are_those_of_the_included_category() {
+        let questions = get_questions();
+        for category in Category::ALL {
+            let eligible_questions =
+                get_eligible_questions(&questions, &get_config_for(category), &LearnStates::new());
+            assert_eq!(eligible_questions.len(), 2);
+            assert!(eligible_questions
+                .iter()
+                .all(|question| question.category == category));
+        }
+    }
+
+    #[test]
+    fn no_included_category_means_no_eligible_questions() {
+        let questions = get_questions();
+        let mut config = get_config_for(Category::V);
+        config.include_v = false;
+
+        assert!(config.all_includes_false());
+        assert!(get_eligible_questions(&questions, &config, &LearnStates::new()).is_empty());
+    }
+
+    #[test]
+    fn excluded_sections_are_not_eligible() {
+        let questions = get_questions();
+        let mut config = get_config_for(Category::N);
+        config.excluded_sections = vec![vec![String::from("N"), String::from("NA101")]];
+
+        let eligible_questions = get_eligible_questions(&questions, &config, &LearnStates::new());
+        assert_eq!(eligible_questions.len(), 1);
+        assert_eq!(eligible_questions[0].identifier, "NA102");
+    }
+
+    #[test]
+    fn active_deck_replaces_the_categories() {
+        let questions = get_questions();
+        let mut config = get_config_for(Category::V);
+        let mut deck = Deck::new("Technik");
+        deck.categories = vec![Category::E, Category::A];
+        config.decks.push(deck);
+        config.active_deck = Some(String::from("Technik"));
+
+        let eligible_questions = get_eligible_questions(&questions, &config, &LearnStates::new());
+        let identifiers: Vec<&str> = eligible_questions
+            .iter()
+            .map(|question| question.identifier.as_str())
+            .collect();
+        assert_eq!(identifiers, ["EA101", "EA102", "AA101", "AA102"]);
+    }
+}
@@ -0,0 +1,581 @@
+//! This module contains the scheduling algorithms which choose the next
+//! question if no question filter applies (see the learning module's
+//! get_next_print_question()).
+//!
+//! All algorithms implement the Scheduler trait and can be chosen in the
+//! Config. Currently, the following algorithms exist:
+//! * Legacy: Funkfragenhelfer's original algorithm with a fixed choosing
+//!   probability per learning "bin"
+//! * Leitner: Time-based Leitner system, in which the interval until a
+//!   question is due again doubles with each bin
+//! * SM-2: The SuperMemo 2 algorithm with an ease factor and interval
+//!   per question
+//! * FSRS: The "Free Spaced Repetition Scheduler" (version 4.5) with a
+//!   stability and difficulty per question
+//!
+//! As Funkfragenhelfer only knows correct and wrong answers, a correct answer
+//! is rated as "good" and a wrong answer as "again" (or with the quality 4 and
+//! 1 in SM-2). The algorithm-specific fields of all algorithms are updated with
+//! each answer, so that one can switch between them at any time. For older
+//! LearnStates without these fields, the fields are derived from the general
+//! LearnState fields (see migrate_learn_states()).
+
+// IMPORTS SECTION //
+use crate::config::{Config, SchedulerMode};
+use crate::helper;
+use crate::learning::{LearnState, LearnStates};
+use crate::question::Question;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+// CONSTANTS SECTION //
+/// Number of seconds per day
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Initial SM-2 ease factor
+const SM2_INITIAL_EASE_FACTOR: f64 = 2.5;
+/// Minimal SM-2 ease factor
+const SM2_MIN_EASE_FACTOR: f64 = 1.3;
+/// Default FSRS 4.5 model weights
+const FSRS_WEIGHTS: [f64; 17] = [
+    0.4072, 1.1829, 3.1262, 15.4722, 7.2102, 0.5316, 1.0651, 0.0234, 1.616, 0.1544, 1.0824, 1.9813,
+    0.0953, 0.2975, 2.2042, 0.2407, 2.9466,
+];
+/// FSRS 4.5 forgetting curve decay
+const FSRS_DECAY: f64 = -0.5;
+/// FSRS 4.5 forgetting curve factor, chosen so that the retrievability
+/// is 90 % after "stability" days
+const FSRS_FACTOR: f64 = 19.0 / 81.0;
+
+// STRUCTS SECTION //
+/// The SM-2-specific learning state of a question.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Sm2State {
+    /// The ease factor, i.e. the factor by which the interval grows
+    pub ease_factor: f64,
+    /// The current interval in days
+    pub interval_in_days: u64,
+    /// Number of correct answers in a row
+    pub repetitions: u64,
+}
+
+impl Default for Sm2State {
+    fn default() -> Sm2State {
+        Sm2State {
+            ease_factor: SM2_INITIAL_EASE_FACTOR,
+            interval_in_days: 0,
+            repetitions: 0,
+        }
+    }
+}
+
+/// The FSRS-specific learning state of a question.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct FsrsState {
+    /// The stability in days, i.e. the time after which the probability
+    /// of a correct answer falls to 90 % (0 if never answered)
+    pub stability: f64,
+    /// The difficulty between 1 (easiest) and 10 (hardest)
+    pub difficulty: f64,
+}
+
+/// The original Funkfragenhelfer algorithm.
+pub struct LegacyScheduler;
+/// The time-based Leitner system.
+pub struct LeitnerScheduler;
+/// The SuperMemo 2 algorithm.
+pub struct Sm2Scheduler;
+/// The Free Spaced Repetition Scheduler (version 4.5).
+pub struct FsrsScheduler;
+
+// TRAITS SECTION //
+/// Common interface of all scheduling algorithms.
+pub trait Scheduler {
+    /// Returns the UNIX time from which on the question with the given LearnState
+    /// is due, or None if the algorithm has no notion of due times.
+    fn get_due_time(&self, learn_state: &LearnState) -> Option<u64>;
+
+    /// Updates the algorithm-specific fields of the given LearnState after an answer.
+    /// This is called before the general fields (such as the bin and the time of the
+    /// last answer) are updated.
+    fn update(&self, _learn_state: &mut LearnState, _is_correct: bool, _unixtime: u64) {}
+
+    /// Derives the algorithm-specific fields of the given LearnState from its general
+    /// fields if the question was answered but the fields were never set (i.e., the
+    /// LearnState stems from a version without this algorithm).
+    fn migrate(&self, _learn_state: &mut LearnState) {}
+
+    /// Chooses the index of the next question out of the given eligible questions.
+    ///
+    /// By default, one of the due questions is chosen randomly. If no question is due,
+    /// the question which is due next is chosen, so that one can always continue
+    /// learning. If the Config's only_due_today is set, only answered questions which
+    /// are due today are considered, as long as such questions exist.
+    fn choose_index(
+        &self,
+        eligible_questions: &[Question],
+        learning: &LearnStates,
+        config: &Config,
+        rng: &mut dyn RngCore,
+    ) -> usize {
+        let now = helper::get_current_unixtime_in_sec();
+        let new_learn_state = LearnState::new();
+        let due_times: Vec<u64> = eligible_questions
+            .iter()
+            .map(|question| {
+                learning
+                    .get(&question.identifier)
+                    .unwrap_or(&new_learn_state)
+            })
+            .map(|learn_state| self.get_due_time(learn_state).unwrap_or(0))
+            .collect();
+
+        if config.only_due_today {
+            let due_today_indexes: Vec<usize> =
+                get_due_today_questions(eligible_questions, learning, self)
+                    .iter()
+                    .filter_map(|due_question| {
+                        eligible_questions
+                            .iter()
+                            .position(|question| question.identifier == due_question.identifier)
+                    })
+                    .collect();
+            if let Some(index) = due_today_indexes.choose(rng) {
+                return *index;
+            }
+        }
+
+        let due_indexes: Vec<usize> = (0..due_times.len())
+            .filter(|index| due_times[*index] <= now)
+            .collect();
+        match due_indexes.choose(rng) {
+            Some(index) => *index,
+            None => (0..due_times.len())
+                .min_by_key(|index| due_times[*index])
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl Scheduler for LegacyScheduler {
+    fn get_due_time(&self, _learn_state: &LearnState) -> Option<u64> {
+        None
+    }
+
+    /// Starting with a random question, look at the question at the current index.
+    /// The higher its bin, the lower the probability is chosen. If not chosen,
+    /// proceed with the next question. This is repeated until a question is chosen.
+    fn choose_index(
+        &self,
+        eligible_questions: &[Question],
+        learning: &LearnStates,
+        _config: &Config,
+        rng: &mut dyn RngCore,
+    ) -> usize {
+        let mut index = rng.gen_range(0..eligible_questions.len());
+        loop {
+            let current_bin = learning
+                .get(&eligible_questions[index].identifier)
+                .map_or(0, |learn_state| learn_state.current_bin);
+            // Look at the question's bin and get the associated choosing
+            // probability (the higher the bin, the lower probability)
+            let threshold = match current_bin {
+                1 => 5,
+                2 => 40,
+                3 => 70,
+                4 => 80,
+                5 => 85,
+                _ => 0,
+            };
+            // If the threshold is *lower* than a random value, choose the question
+            if rng.gen_range(0..=100) > threshold {
+                return index;
+            }
+            index = (index + 1) % eligible_questions.len();
+        }
+    }
+}
+
+impl Scheduler for LeitnerScheduler {
+    fn get_due_time(&self, learn_state: &LearnState) -> Option<u64> {
+        if learn_state.current_bin == 0 {
+            Some(0)
+        } else {
+            Some(
+                learn_state.time_last_answer + get_leitner_interval_in_sec(learn_state.current_bin),
+            )
+        }
+    }
+}
+
+impl Scheduler for Sm2Scheduler {
+    fn get_due_time(&self, learn_state: &LearnState) -> Option<u64> {
+        Some(learn_state.time_last_answer + learn_state.sm2.interval_in_days * SECONDS_PER_DAY)
+    }
+
+    fn update(&self, learn_state: &mut LearnState, is_correct: bool, _unixtime: u64) {
+        let sm2 = &mut learn_state.sm2;
+        let quality: f64 = if is_correct { 4.0 } else { 1.0 };
+        if is_correct {
+            sm2.interval_in_days = match sm2.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (sm2.interval_in_days as f64 * sm2.ease_factor).round() as u64,
+            };
+            sm2.repetitions += 1;
+        } else {
+            sm2.interval_in_days = 1;
+            sm2.repetitions = 0;
+        }
+        sm2.ease_factor = (sm2.ease_factor
+            + (0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02)))
+            .max(SM2_MIN_EASE_FACTOR);
+    }
+
+    /// The interval is taken from the Leitner interval of the question's bin, the
+    /// repetitions are the correct answers since the last wrong one, and the ease
+    /// factor is reduced for each wrong answer as SM-2 would have done.
+    fn migrate(&self, learn_state: &mut LearnState) {
+        let is_answered = learn_state.correct + learn_state.wrong > 0;
+        let is_unset = learn_state.sm2.repetitions == 0 && learn_state.sm2.interval_in_days == 0;
+        if !is_answered || !is_unset {
+            return;
+        }
+        learn_state.sm2 = Sm2State {
+            ease_factor: (SM2_INITIAL_EASE_FACTOR - 0.54 * learn_state.wrong as f64)
+                .max(SM2_MIN_EASE_FACTOR),
+            interval_in_days: (get_leitner_interval_in_sec(learn_state.current_bin)
+                / SECONDS_PER_DAY)
+                .max(1),
+            repetitions: learn_state.rounds_since_wrong,
+        };
+    }
+}
+
+impl FsrsScheduler {
+    /// Returns the initial difficulty for the given rating (1: again, 3: good).
+    fn get_initial_difficulty(rating: f64) -> f64 {
+        (FSRS_WEIGHTS[4] - (rating - 3.0) * FSRS_WEIGHTS[5]).clamp(1.0, 10.0)
+    }
+}
+
+impl Scheduler for FsrsScheduler {
+    fn get_due_time(&self, learn_state: &LearnState) -> Option<u64> {
+        if learn_state.fsrs.stability <= 0.0 {
+            return Some(0);
+        }
+        // With a desired retention of 90 %, the interval equals the stability
+        let interval_in_days = learn_state.fsrs.stability.round().max(1.0) as u64;
+        Some(learn_state.time_last_answer + interval_in_days * SECONDS_PER_DAY)
+    }
+
+    fn update(&self, learn_state: &mut LearnState, is_correct: bool, unixtime: u64) {
+        let rating: f64 = if is_correct { 3.0 } else { 1.0 };
+        let fsrs = &mut learn_state.fsrs;
+        if fsrs.stability <= 0.0 {
+            fsrs.stability = FSRS_WEIGHTS[rating as usize - 1];
+            fsrs.difficulty = FsrsScheduler::get_initial_difficulty(rating);
+            return;
+        }
+
+        let elapsed_days =
+            unixtime.saturating_sub(learn_state.time_last_answer) as f64 / SECONDS_PER_DAY as f64;
+        let retrievability = (1.0 + FSRS_FACTOR * elapsed_days / fsrs.stability).powf(FSRS_DECAY);
+        let (stability, difficulty) = (fsrs.stability, fsrs.difficulty);
+        fsrs.stability = if is_correct {
+            stability
+                * (FSRS_WEIGHTS[8].exp()
+                    * (11.0 - difficulty)
+                    * stability.powf(-FSRS_WEIGHTS[9])
+                    * ((FSRS_WEIGHTS[10] * (1.0 - retrievability)).exp() - 1.0)
+                    + 1.0)
+        } else {
+            (FSRS_WEIGHTS[11]
+                * difficulty.powf(-FSRS_WEIGHTS[12])
+                * ((stability + 1.0).powf(FSRS_WEIGHTS[13]) - 1.0)
+                * (FSRS_WEIGHTS[14] * (1.0 - retrievability)).exp())
+            .min(stability)
+        };
+        let new_difficulty = difficulty - FSRS_WEIGHTS[6] * (rating - 3.0);
+        fsrs.difficulty = (FSRS_WEIGHTS[7] * FsrsScheduler::get_initial_difficulty(3.0)
+            + (1.0 - FSRS_WEIGHTS[7]) * new_difficulty)
+            .clamp(1.0, 10.0);
+    }
+
+    /// The stability is taken from the Leitner interval of the question's bin, and
+    /// the difficulty rises from the initial difficulty with the share of wrong answers.
+    fn migrate(&self, learn_state: &mut LearnState) {
+        let answers = learn_state.correct + learn_state.wrong;
+        if answers == 0 || learn_state.fsrs.stability > 0.0 {
+            return;
+        }
+        let wrong_share = learn_state.wrong as f64 / answers as f64;
+        learn_state.fsrs = FsrsState {
+            stability: (get_leitner_interval_in_sec(learn_state.current_bin) / SECONDS_PER_DAY)
+                .max(1) as f64,
+            difficulty: (FsrsScheduler::get_initial_difficulty(3.0)
+                + 2.0 * FSRS_WEIGHTS[6] * wrong_share)
+                .clamp(1.0, 10.0),
+        };
+    }
+}
+
+// PUBLIC FUNCTIONS SECTION //
+/// Returns the scheduler of the given scheduler mode.
+pub fn get_scheduler(scheduler_mode: SchedulerMode) -> Box<dyn Scheduler> {
+    match scheduler_mode {
+        SchedulerMode::Legacy => Box::new(LegacyScheduler),
+        SchedulerMode::Leitner => Box::new(LeitnerScheduler),
+        SchedulerMode::Sm2 => Box::new(Sm2Scheduler),
+        SchedulerMode::Fsrs => Box::new(FsrsScheduler),
+    }
+}
+
+/// Returns the interval (in seconds) after which a question of the given bin is due
+/// again according to the time-based Leitner system.
+///
+/// The interval doubles with each bin, i.e. it is 1 day for bin 1, 2 days for bin 2,
+/// 4 days for bin 3, 8 days for bin 4, 16 days for bin 5 and so on. Never answered
+/// questions (bin 0) are due immediately.
+pub fn get_leitner_interval_in_sec(bin: u64) -> u64 {
+    if bin == 0 {
+        0
+    } else {
+        SECONDS_PER_DAY << (bin - 1).min(16)
+    }
+}
+
+/// Returns the already answered questions out of the given ones which are due until
+/// the end of the current (UTC) day according to the given scheduler, sorted by their
+/// due time. For schedulers without due times, no question is returned.
+pub fn get_due_today_questions<S: Scheduler + ?Sized>(
+    eligible_questions: &[Question],
+    learning: &LearnStates,
+    scheduler: &S,
+) -> Vec<Question> {
+    let end_of_today =
+        (helper::get_current_unixtime_in_sec() / SECONDS_PER_DAY + 1) * SECONDS_PER_DAY;
+    let mut due_today_questions: Vec<(u64, &Question)> = eligible_questions
+        .iter()
+        .filter_map(|question| {
+            let learn_state = learning.get(&question.identifier)?;
+            let due_time = scheduler.get_due_time(learn_state)?;
+            let is_answered = learn_state.correct + learn_state.wrong > 0;
+            (is_answered && due_time < end_of_today).then_some((due_time, question))
+        })
+        .collect();
+    due_today_questions.sort_by_key(|(due_time, _)| *due_time);
+    due_today_questions
+        .into_iter()
+        .map(|(_, question)| question.clone())
+        .collect()
+}
+
+/// Updates the algorithm-specific fields of all schedulers after an answer (see the
+/// Scheduler trait's update()).
+pub fn update_learn_state(learn_state: &mut LearnState, is_correct: bool, unixtime: u64) {
+    for scheduler_mode in SchedulerMode::ALL {
+        let scheduler = get_scheduler(scheduler_mode);
+        scheduler.migrate(learn_state);
+        scheduler.update(learn_state, is_correct, unixtime);
+    }
+}
+
+/// Derives the algorithm-specific fields of all schedulers for LearnStates which
+/// stem from a version without these fields (see the Scheduler trait's migrate()).
+pub fn migrate_learn_states(learning: &mut LearnStates) {
+    for scheduler_mode in SchedulerMode::ALL {
+        let scheduler = get_scheduler(scheduler_mode);
+        for learn_state in learning.values_mut() {
+            scheduler.migrate(learn_state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::question::test_question;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Returns a LearnState which was answered at the given UNIX time and is
+    /// in the given bin.
+    fn get_answered_learn_state(bin: u64, time_last_answer: u64) -> LearnState {
+        LearnState {
+            current_bin: bin,
+            correct: bin,
+            time_last_answer,
+            ..LearnState::new()
+        }
+    }
+
+    #[test]
+    fn leitner_interval_doubles_with_each_bin() {
+        assert_eq!(get_leitner_interval_in_sec(0), 0);
+        assert_eq!(get_leitner_interval_in_sec(1), SECONDS_PER_DAY);
+        assert_eq!(get_leitner_interval_in_sec(2), 2 * SECONDS_PER_DAY);
+        assert_eq!(get_leitner_interval_in_sec(5), 16 * SECONDS_PER_DAY);
+        // Very high bins do not overflow
+        assert_eq!(
+            get_leitner_interval_in_sec(u64::MAX),
+            get_leitner_interval_in_sec(17)
+        );
+    }
+
+    #[test]
+    fn leitner_due_time_follows_the_bin() {
+        assert_eq!(LeitnerScheduler.get_due_time(&LearnState::new()), Some(0));
+        assert_eq!(
+            LeitnerScheduler.get_due_time(&get_answered_learn_state(3, 1000)),
+            Some(1000 + 4 * SECONDS_PER_DAY)
+        );
+        assert_eq!(LegacyScheduler.get_due_time(&LearnState::new()), None);
+    }
+
+    #[test]
+    fn sm2_intervals_grow_with_correct_answers() {
+        let mut learn_state = LearnState::new();
+        let mut intervals = Vec::new();
+        for _ in 0..3 {
+            Sm2Scheduler.update(&mut learn_state, true, 0);
+            intervals.push(learn_state.sm2.interval_in_days);
+        }
+
+        // With the quality 4 of a correct answer, the ease factor stays the same
+        assert_eq!(intervals, vec![1, 6, 15]);
+        assert_eq!(learn_state.sm2.repetitions, 3);
+        assert_eq!(learn_state.sm2.ease_factor, SM2_INITIAL_EASE_FACTOR);
+    }
+
+    #[test]
+    fn sm2_wrong_answer_resets_the_interval_and_lowers_the_ease_factor() {
+        let mut learn_state = LearnState::new();
+        Sm2Scheduler.update(&mut learn_state, true, 0);
+        Sm2Scheduler.update(&mut learn_state, true, 0);
+        Sm2Scheduler.update(&mut learn_state, false, 0);
+
+        assert_eq!(learn_state.sm2.interval_in_days, 1);
+        assert_eq!(learn_state.sm2.repetitions, 0);
+        assert!((learn_state.sm2.ease_factor - 1.96).abs() < 1e-9);
+
+        for _ in 0..10 {
+            Sm2Scheduler.update(&mut learn_state, false, 0);
+        }
+        assert_eq!(learn_state.sm2.ease_factor, SM2_MIN_EASE_FACTOR);
+    }
+
+    #[test]
+    fn fsrs_first_answer_sets_the_initial_stability_and_difficulty() {
+        let mut correct_learn_state = LearnState::new();
+        FsrsScheduler.update(&mut correct_learn_state, true, 0);
+        assert_eq!(correct_learn_state.fsrs.stability, FSRS_WEIGHTS[2]);
+        assert_eq!(correct_learn_state.fsrs.difficulty, FSRS_WEIGHTS[4]);
+
+        let mut wrong_learn_state = LearnState::new();
+        FsrsScheduler.update(&mut wrong_learn_state, false, 0);
+        assert_eq!(wrong_learn_state.fsrs.stability, FSRS_WEIGHTS[0]);
+        assert!(wrong_learn_state.fsrs.difficulty > correct_learn_state.fsrs.difficulty);
+    }
+
+    #[test]
+    fn fsrs_stability_rises_when_correct_and_falls_when_wrong() {
+        let mut learn_state = LearnState::new();
+        FsrsScheduler.update(&mut learn_state, true, 0);
+        let initial_stability = learn_state.fsrs.stability;
+
+        // Answered correctly when it is due
+        let due_time = FsrsScheduler.get_due_time(&learn_state).unwrap();
+        FsrsScheduler.update(&mut learn_state, true, due_time);
+        learn_state.time_last_answer = due_time;
+        let raised_stability = learn_state.fsrs.stability;
+        assert!(raised_stability > initial_stability);
+
+        FsrsScheduler.update(&mut learn_state, false, due_time + SECONDS_PER_DAY);
+        assert!(learn_state.fsrs.stability < raised_stability);
+        assert!((1.0..=10.0).contains(&learn_state.fsrs.difficulty));
+    }
+
+    #[test]
+    fn migration_derives_the_fields_from_the_general_fields() {
+        let mut learning = LearnStates::new();
+        learning.insert(
+            String::from("NA001"),
+            LearnState {
+                current_bin: 3,
+                correct: 3,
+                wrong: 1,
+                rounds_since_wrong: 2,
+                ..LearnState::new()
+            },
+        );
+        learning.insert(String::from("NA002"), LearnState::new());
+        migrate_learn_states(&mut learning);
+
+        let learn_state = &learning["NA001"];
+        assert_eq!(learn_state.sm2.interval_in_days, 4);
+        assert_eq!(learn_state.sm2.repetitions, 2);
+        assert!((learn_state.sm2.ease_factor - 1.96).abs() < 1e-9);
+        assert_eq!(learn_state.fsrs.stability, 4.0);
+        // Never answered questions keep their default fields
+        assert_eq!(learning["NA002"].sm2.interval_in_days, 0);
+        assert_eq!(learning["NA002"].fsrs.stability, 0.0);
+    }
+
+    #[test]
+    fn due_questions_are_chosen_first() {
+        let questions = vec![test_question("NA001", &[]), test_question("NA002", &[])];
+        let mut learning = LearnStates::new();
+        let now = helper::get_current_unixtime_in_sec();
+        learning.insert(String::from("NA001"), get_answered_learn_state(5, now));
+        let config = Config::new();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            assert_eq!(
+                LeitnerScheduler.choose_index(&questions, &learning, &config, &mut rng),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn question_due_next_is_chosen_if_none_is_due() {
+        let questions = vec![test_question("NA001", &[]), test_question("NA002", &[])];
+        let mut learning = LearnStates::new();
+        let now = helper::get_current_unixtime_in_sec();
+        learning.insert(String::from("NA001"), get_answered_learn_state(5, now));
+        learning.insert(String::from("NA002"), get_answered_learn_state(2, now));
+        let config = Config::new();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(
+            LeitnerScheduler.choose_index(&questions, &learning, &config, &mut rng),
+            1
+        );
+    }
+
+    #[test]
+    fn due_today_questions_are_answered_and_sorted_by_due_time() {
+        let questions = vec![
+            test_question("NA001", &[]),
+            test_question("NA002", &[]),
+            test_question("NA003", &[]),
+            test_question("NA004", &[]),
+        ];
+        let mut learning = LearnStates::new();
+        let now = helper::get_current_unixtime_in_sec();
+        learning.insert(String::from("NA001"), get_answered_learn_state(1, 2000));
+        learning.insert(String::from("NA002"), get_answered_learn_state(1, 1000));
+        // Due in 16 days
+        learning.insert(String::from("NA003"), get_answered_learn_state(5, now));
+        // NA004 was never answered
+
+        let due_today_identifiers: Vec<String> =
+            get_due_today_questions(&questions, &learning, &LeitnerScheduler)
+                .into_iter()
+                .map(|question| question.identifier)
+                .collect();
+        assert_eq!(due_today_identifiers, vec!["NA002", "NA001"]);
+        assert!(get_due_today_questions(&questions, &learning, &LegacyScheduler).is_empty());
+    }
+}
@@ -0,0 +1,385 @@
+//! This module contains the free-text search across all questions.
+//!
+//! The SearchIndex covers each question's identifier, question text and all
+//! four answers. In addition, the user's notes on the questions (see the
+//! learning module's LearnState) are searched, which are taken from the current
+//! LearnStates with each search, as they can change at any time. Texts and
+//! queries are normalized the same way before they are compared, so that the
+//! search is case-, accent- and LaTeX-insensitive: The formulas are converted
+//! into plain Unicode (see the math module), superscripts and subscripts become
+//! normal characters, accents are removed (e.g. "ü" becomes "u"), LaTeX markup
+//! characters are dropped and whitespace is collapsed. Hence, e.g. the queries
+//! "10^-6" and "10^{-6}" find "$10^{-6}$", "\Omega" finds "Ω" and "Ubertragung"
+//! finds "Übertragung".
+
+// IMPORTS SECTION //
+use crate::learning::LearnStates;
+use crate::math;
+use crate::question::{Category, Question};
+
+// CONSTANTS SECTION //
+/// Number of characters shown before and after a match in a snippet
+const SNIPPET_CONTEXT: usize = 40;
+
+// STRUCT SECTION //
+/// A question which matches a search query.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SearchResult {
+    /// The question's identifier
+    pub identifier: String,
+    /// The question's main category
+    pub category: Category,
+    /// An excerpt of the question or answer text (or of the user's note) around the match
+    pub snippet: String,
+}
+
+/// The search index of a set of questions.
+pub struct SearchIndex {
+    entries: Vec<IndexEntry>,
+}
+
+/// The indexed texts of a single question.
+struct IndexEntry {
+    identifier: String,
+    category: Category,
+    /// The normalized identifier
+    normalized_identifier: String,
+    /// The question text and the answers (in this order)
+    fields: Vec<IndexField>,
+}
+
+/// An indexed text together with its normalized form.
+struct IndexField {
+    /// The text in plain Unicode (as shown in snippets)
+    chars: Vec<char>,
+    /// The normalized text
+    normalized: String,
+    /// For each character of the normalized text, the index of the
+    /// character in chars from which it originates
+    positions: Vec<usize>,
+}
+
+impl SearchIndex {
+    /// Creates the search index of the given questions.
+    pub fn new(questions: &[Question]) -> SearchIndex {
+        let entries = questions
+            .iter()
+            .map(|question| IndexEntry {
+                identifier: question.identifier.clone(),
+                category: question.category,
+                normalized_identifier: normalize(&question.identifier),
+                fields: [
+                    &question.question,
+                    &question.answer_a,
+                    &question.answer_b,
+                    &question.answer_c,
+                    &question.answer_d,
+                ]
+                .iter()
+                .map(|text| IndexField::new(text))
+                .collect(),
+            })
+            .collect();
+        SearchIndex { entries }
+    }
+
+    /// Searches the questions which contain all words of the given query (each in
+    /// its identifier, question text, one of its answers or the user's note).
+    ///
+    /// ### Arguments
+    /// * query: The search query, whose words are separated by whitespace
+    /// * learn_states: The current LearnStates, which contain the user's notes
+    ///
+    /// ### Return value
+    /// * The matching questions; Questions whose identifier matches the query are
+    ///   listed first, all others in the order of the indexed questions. An empty
+    ///   query matches no question.
+    pub fn search(&self, query: &str, learn_states: &LearnStates) -> Vec<SearchResult> {
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(|word| {
+                // Query words with LaTeX commands are read as formulas, even
+                // without the surrounding "$"
+                if word.contains('\\') && !word.contains('$') {
+                    normalize(&format!("${}$", word))
+                } else {
+                    normalize(word)
+                }
+            })
+            .filter(|word| !word.is_empty())
+            .collect();
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let normalized_query = words.concat();
+
+        let mut ranked_results = Vec::new();
+        for entry in &self.entries {
+            let note_field = learn_states
+                .get(&entry.identifier)
+                .filter(|learn_state| !learn_state.note.is_empty())
+                .map(|learn_state| IndexField::new(&learn_state.note));
+            let is_match = words.iter().all(|word| {
+                entry.normalized_identifier.contains(word.as_str())
+                    || entry
+                        .fields
+                        .iter()
+                        .chain(&note_field)
+                        .any(|field| field.normalized.contains(word.as_str()))
+            });
+            if !is_match {
+                continue;
+            }
+            let rank = if entry.normalized_identifier == normalized_query {
+                0
+            } else if entry.normalized_identifier.starts_with(&normalized_query) {
+                1
+            } else {
+                2
+            };
+            ranked_results.push((rank, entry.get_result(&words, note_field.as_ref())));
+        }
+        // The sort is stable, so the order of the questions is kept within a rank
+        ranked_results.sort_by_key(|(rank, _)| *rank);
+        ranked_results
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+}
+
+impl IndexEntry {
+    /// Returns the search result of this entry with a snippet around the first
+    /// match of the given (normalized) words in the question or answer texts, or
+    /// else in the given note.
+    fn get_result(&self, words: &[String], note_field: Option<&IndexField>) -> SearchResult {
+        let snippet = words
+            .iter()
+            .find_map(|word| self.fields.iter().find_map(|field| field.get_snippet(word)))
+            .or_else(|| {
+                let note_field = note_field?;
+                let note_snippet = words.iter().find_map(|word| note_field.get_snippet(word))?;
+                Some(format!("Notiz: {}", note_snippet))
+            })
+            .unwrap_or_else(|| self.fields[0].get_start_snippet());
+        SearchResult {
+            identifier: self.identifier.clone(),
+            category: self.category,
+            snippet,
+        }
+    }
+}
+
+impl IndexField {
+    /// Creates the indexed field of the given question or answer text.
+    fn new(text: &str) -> IndexField {
+        let chars: Vec<char> = math::to_unicode(text).chars().collect();
+        let (normalized, positions) = normalize_chars(&chars);
+        IndexField {
+            chars,
+            normalized,
+            positions,
+        }
+    }
+
+    /// Returns the snippet around the first match of the given normalized
+    /// word, or None if the word does not occur in this field.
+    fn get_snippet(&self, word: &str) -> Option<String> {
+        let byte_index = self.normalized.find(word)?;
+        let start_index = self.normalized[..byte_index].chars().count();
+        let end_index = start_index + word.chars().count() - 1;
+        let start = self.positions[start_index];
+        let end = self.positions[end_index] + 1;
+        Some(self.get_excerpt(
+            start.saturating_sub(SNIPPET_CONTEXT),
+            (end + SNIPPET_CONTEXT).min(self.chars.len()),
+        ))
+    }
+
+    /// Returns the snippet of the beginning of this field.
+    fn get_start_snippet(&self) -> String {
+        self.get_excerpt(0, (2 * SNIPPET_CONTEXT).min(self.chars.len()))
+    }
+
+    /// Returns the text between the given character indices, with an ellipsis
+    /// on each side on which the text is cut.
+    fn get_excerpt(&self, start: usize, end: usize) -> String {
+        let mut excerpt: String = self.chars[start..end].iter().collect();
+        excerpt = excerpt.trim().to_string();
+        if start > 0 {
+            excerpt.insert(0, '…');
+        }
+        if end < self.chars.len() {
+            excerpt.push('…');
+        }
+        excerpt
+    }
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Normalizes the given text for the search (see this module's comment).
+pub fn normalize(text: &str) -> String {
+    let chars: Vec<char> = math::to_unicode(text).chars().collect();
+    normalize_chars(&chars).0
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Normalizes the given characters of a text in plain Unicode (see normalize()).
+///
+/// ### Return value
+/// * The normalized text, together with the index of the original character of
+///   each normalized character
+fn normalize_chars(chars: &[char]) -> (String, Vec<usize>) {
+    let mut normalized = String::new();
+    let mut positions = Vec::new();
+    for (index, c) in chars.iter().enumerate() {
+        if c.is_whitespace() {
+            if !normalized.is_empty() && !normalized.ends_with(' ') {
+                normalized.push(' ');
+                positions.push(index);
+            }
+            continue;
+        }
+        for lowercase_c in c.to_lowercase() {
+            for normalized_c in fold_char(lowercase_c).chars() {
+                normalized.push(normalized_c);
+                positions.push(index);
+            }
+        }
+    }
+    (normalized, positions)
+}
+
+/// Folds the given lowercase character into its searchable form, i.e. removes
+/// accents, replaces superscripts, subscripts and math operators by their
+/// normal forms, and drops LaTeX markup characters.
+fn fold_char(c: char) -> String {
+    let folded = match c {
+        'ä' | 'à' | 'á' | 'â' | 'ã' | 'å' => "a",
+        'ç' => "c",
+        'è' | 'é' | 'ê' | 'ë' => "e",
+        'ì' | 'í' | 'î' | 'ï' => "i",
+        'ñ' => "n",
+        'ö' | 'ò' | 'ó' | 'ô' | 'õ' | 'ø' => "o",
+        'ü' | 'ù' | 'ú' | 'û' => "u",
+        'ß' => "ss",
+        '⁰' | '₀' => "0",
+        '¹' | '₁' => "1",
+        '²' | '₂' => "2",
+        '³' | '₃' => "3",
+        '⁴' | '₄' => "4",
+        '⁵' | '₅' => "5",
+        '⁶' | '₆' => "6",
+        '⁷' | '₇' => "7",
+        '⁸' | '₈' => "8",
+        '⁹' | '₉' => "9",
+        '⁺' | '₊' => "+",
+        '⁻' | '₋' | '−' => "-",
+        '·' | '×' => "*",
+        '^' | '_' | '{' | '}' | '\\' | '$' => "",
+        c => return c.to_string(),
+    };
+    folded.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::LearnState;
+    use crate::question::test_question;
+
+    /// Returns a question with the given identifier and question text.
+    fn get_question(identifier: &str, question: &str) -> Question {
+        Question {
+            question: question.to_string(),
+            ..test_question(identifier, &[])
+        }
+    }
+
+    /// Returns the identifiers of the questions which match the given query.
+    fn search(index: &SearchIndex, query: &str, learn_states: &LearnStates) -> Vec<String> {
+        index
+            .search(query, learn_states)
+            .into_iter()
+            .map(|result| result.identifier)
+            .collect()
+    }
+
+    #[test]
+    fn normalization_ignores_case_accents_and_whitespace() {
+        assert_eq!(normalize("Übertragung"), "ubertragung");
+        assert_eq!(normalize("  Große   Straße "), "grosse strasse ");
+        assert_eq!(normalize("Café"), "cafe");
+    }
+
+    #[test]
+    fn normalization_ignores_the_formula_notation() {
+        assert_eq!(normalize("$10^{-6}$"), normalize("10^-6"));
+        assert_eq!(normalize("10⁻⁶"), normalize("10^-6"));
+        assert_eq!(normalize("$\\Omega$"), "ω");
+        assert_eq!(normalize("$U_{eff}$"), "ueff");
+    }
+
+    #[test]
+    fn search_finds_the_questions_with_all_words() {
+        let questions = vec![
+            get_question("NA101", "Welche Übertragung ist $10^{-6}$ s lang?"),
+            get_question("NA102", "Was ist ein Widerstand von 50 $\\Omega$?"),
+        ];
+        let index = SearchIndex::new(&questions);
+        let learn_states = LearnStates::new();
+
+        assert_eq!(search(&index, "ubertragung", &learn_states), vec!["NA101"]);
+        assert_eq!(search(&index, "10^-6", &learn_states), vec!["NA101"]);
+        assert_eq!(search(&index, "\\Omega", &learn_states), vec!["NA102"]);
+        assert_eq!(search(&index, "ist", &learn_states).len(), 2);
+        assert!(search(&index, "Widerstand Übertragung", &learn_states).is_empty());
+        assert!(search(&index, "   ", &learn_states).is_empty());
+    }
+
+    #[test]
+    fn matching_identifiers_are_listed_first() {
+        let questions = vec![
+            get_question("NA101", "Siehe NA102"),
+            get_question("NA102", "Frage"),
+        ];
+        let index = SearchIndex::new(&questions);
+
+        assert_eq!(
+            search(&index, "na102", &LearnStates::new()),
+            vec!["NA102", "NA101"]
+        );
+    }
+
+    #[test]
+    fn notes_are_searched_and_shown_in_the_snippet() {
+        let questions = vec![get_question("NA101", "Frage")];
+        let index = SearchIndex::new(&questions);
+        let mut learn_states = LearnStates::new();
+        assert!(search(&index, "eselsbrucke", &learn_states).is_empty());
+
+        learn_states.insert(
+            String::from("NA101"),
+            LearnState {
+                note: String::from("Eselsbrücke: P = U²/R"),
+                ..LearnState::new()
+            },
+        );
+        let results = index.search("eselsbrucke", &learn_states);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippet, "Notiz: Eselsbrücke: P = U²/R");
+    }
+
+    #[test]
+    fn snippet_is_cut_around_the_match() {
+        let text = format!("{} Treffer {}", "x".repeat(100), "y".repeat(100));
+        let questions = vec![get_question("NA101", &text)];
+        let index = SearchIndex::new(&questions);
+
+        let results = index.search("treffer", &LearnStates::new());
+        let snippet = &results[0].snippet;
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("Treffer"));
+        assert!(snippet.chars().count() < text.chars().count());
+    }
+}
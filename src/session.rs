@@ -0,0 +1,458 @@
+//! This module contains the learning session, which is the API that
+//! Funkfragenhelfer's front ends (the GUI, the command-line mode and the
+//! terminal UI) drive.
+//!
+//! A Session owns the Config, the LearnStates and the questions, and keeps the
+//! state of the practice loop: the current question, whether and how it was
+//! answered, and the statistics. Answering a question updates the LearnStates
+//! (see the learning and scheduler modules), records the answer in the answer
+//! history (see the history module) and saves the LearnStates. Hence, a front end
+//! only has to show the session's state and forward the user's input.
+
+// IMPORTS SECTION //
+use crate::config::Config;
+use crate::error::{FfhError, Result};
+use crate::exam::{Exam, ExamPart, ExamResult};
+use crate::history::{self, AnswerEvent, AnswerMode};
+use crate::learning::{self, Answer, LearnStates, PrintQuestion, Statistics};
+use crate::question::{self, Question};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::Instant;
+
+// STRUCT SECTION //
+/// A learning session, i.e. the practice loop of one user.
+pub struct Session {
+    /// The current Config. After a change of the question selection (e.g. the
+    /// categories or sections), call update_eligible_questions().
+    pub config: Config,
+    /// The current LearnStates
+    pub learn_states: LearnStates,
+    /// All questions of the catalog
+    questions: Vec<Question>,
+    /// The questions which are chosen by the Config
+    eligible_questions: Vec<Question>,
+    /// The current question with its shuffled answers
+    print_question: PrintQuestion,
+    /// The index of the given (shuffled) answer, if the current question was answered
+    given_answer: Option<usize>,
+    /// The time at which the current question was shown
+    question_start_time: Instant,
+    correct_answers_since_start: usize,
+    answers_since_start: usize,
+    /// The statistics of the eligible questions
+    statistics: Statistics,
+    /// The random number generator for choosing the practice questions and
+    /// shuffling their answers
+    rng: StdRng,
+    /// The random number generator for drawing the exam questions; It is separate
+    /// from rng, so that a seeded exam does not depend on the practice before it
+    exam_rng: StdRng,
+    /// A message about a change of the Config by the session itself (e.g. a
+    /// deactivated study deck), which was not shown to the user yet
+    message: Option<String>,
+}
+
+impl Session {
+    /// Creates a new Session, starting with the next question to ask. If the
+    /// Config's active study deck contains no question (e.g. as its questions
+    /// left its bin range), the deck is deactivated and the user is told so (see
+    /// take_message()).
+    ///
+    /// ### Arguments
+    /// * config: The current Funkfragenhelfer configuration
+    /// * learn_states: The current question LearnStates
+    /// * questions: The set of all questions that can be asked
+    /// * seed: The seed of the random number generators, or None for random ones;
+    ///   With the same seed (and the same LearnStates), the same questions are
+    ///   asked in the same order with the same shuffled answers, and the same
+    ///   exams are drawn
+    ///
+    /// ### Return value
+    /// * The new Session, or None if the Config chooses no question at all
+    pub fn new(
+        mut config: Config,
+        learn_states: LearnStates,
+        questions: Vec<Question>,
+        seed: Option<u64>,
+    ) -> Option<Session> {
+        let mut eligible_questions =
+            question::get_eligible_questions(&questions, &config, &learn_states);
+        let mut message = None;
+        if eligible_questions.is_empty() {
+            if let Some(deck_name) = config.active_deck.take() {
+                message = Some(get_deck_deactivated_message(&deck_name));
+                eligible_questions =
+                    question::get_eligible_questions(&questions, &config, &learn_states);
+            }
+        }
+        if eligible_questions.is_empty() {
+            return None;
+        }
+        let (mut rng, exam_rng) = match seed {
+            Some(seed) => (
+                StdRng::seed_from_u64(seed),
+                StdRng::seed_from_u64(seed.wrapping_add(1)),
+            ),
+            None => (StdRng::from_entropy(), StdRng::from_entropy()),
+        };
+        let print_question = learning::get_next_print_question(
+            &eligible_questions,
+            &learn_states,
+            &config,
+            &mut rng,
+        );
+        let statistics = Statistics::new(&eligible_questions, &learn_states, &config);
+        Some(Session {
+            config,
+            learn_states,
+            questions,
+            eligible_questions,
+            print_question,
+            given_answer: None,
+            question_start_time: Instant::now(),
+            correct_answers_since_start: 0,
+            answers_since_start: 0,
+            statistics,
+            rng,
+            exam_rng,
+            message,
+        })
+    }
+
+    /// Returns all questions of the catalog.
+    pub fn get_questions(&self) -> &[Question] {
+        &self.questions
+    }
+
+    /// Returns the questions which are chosen by the Config.
+    pub fn get_eligible_questions(&self) -> &[Question] {
+        &self.eligible_questions
+    }
+
+    /// Returns the current question with its shuffled answers.
+    pub fn get_print_question(&self) -> &PrintQuestion {
+        &self.print_question
+    }
+
+    /// Returns the index of the given (shuffled) answer, or None if the current
+    /// question was not answered yet.
+    pub fn get_given_answer(&self) -> Option<usize> {
+        self.given_answer
+    }
+
+    /// Returns whether or not the given answer is correct, or None if the current
+    /// question was not answered yet.
+    pub fn is_given_answer_correct(&self) -> Option<bool> {
+        self.given_answer
+            .map(|given_answer| self.print_question.answer_shuffle[given_answer] == Answer::A)
+    }
+
+    /// Returns the statistics of the eligible questions.
+    pub fn get_statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Returns the number of correctly answered questions since the session started.
+    pub fn get_correct_answers_since_start(&self) -> usize {
+        self.correct_answers_since_start
+    }
+
+    /// Returns the number of answered questions since the session started.
+    pub fn get_answers_since_start(&self) -> usize {
+        self.answers_since_start
+    }
+
+    /// Returns the message about a change of the Config by the session itself
+    /// (e.g. a deactivated study deck), if there is one which was not shown yet.
+    /// Thereby, the message is removed, so that it is shown only once.
+    pub fn take_message(&mut self) -> Option<String> {
+        self.message.take()
+    }
+
+    /// Answers the current question with the given (shuffled) answer. Thereby, the
+    /// answer is recorded in the answer history and the LearnStates are updated and
+    /// saved. If the current question was already answered, nothing happens.
+    ///
+    /// ### Return value
+    /// * Nothing, an FfhError::InvalidAnswer if the question has no such answer (i.e.,
+    ///   given_answer is greater than 3; the question stays unanswered), or an FfhError
+    ///   if the answer could not be recorded or saved (the LearnStates are updated
+    ///   nonetheless, and are saved again with the next change)
+    pub fn answer(&mut self, given_answer: usize) -> Result<()> {
+        if self.given_answer.is_some() {
+            return Ok(());
+        }
+        if given_answer >= self.print_question.answer_shuffle.len() {
+            return Err(FfhError::InvalidAnswer {
+                index: given_answer,
+            });
+        }
+        self.given_answer = Some(given_answer);
+        let response_time_in_ms = self.question_start_time.elapsed().as_millis() as u64;
+        let event = AnswerEvent::new(
+            &self.print_question,
+            given_answer,
+            Some(response_time_in_ms),
+            AnswerMode::Practice,
+        );
+        let history_result = history::append_event(&event, &self.config);
+
+        let identifier = &self.print_question.question.identifier;
+        self.answers_since_start += 1;
+        if event.is_correct {
+            self.correct_answers_since_start += 1;
+            learning::handle_correct_answer(&mut self.learn_states, identifier, &self.config);
+        } else {
+            learning::handle_wrong_answer(
+                &mut self.learn_states,
+                identifier,
+                event.original_answer,
+            );
+        }
+        let save_result = self.save_learning();
+        let update_result = self.update_learning_dependents();
+        history_result.and(save_result).and(update_result)
+    }
+
+    /// Continues with the next question, either after an answer or to skip
+    /// the current question.
+    pub fn next_question(&mut self) {
+        self.print_question = learning::get_next_print_question(
+            &self.eligible_questions,
+            &self.learn_states,
+            &self.config,
+            &mut self.rng,
+        );
+        self.given_answer = None;
+        self.question_start_time = Instant::now();
+    }
+
+    /// Continues with the question with the given identifier (e.g. a search
+    /// result), regardless of whether or not it is eligible or due.
+    ///
+    /// ### Return value
+    /// * Whether or not a question with the given identifier exists
+    pub fn practice_question(&mut self, identifier: &str) -> bool {
+        let Some(question) = self
+            .questions
+            .iter()
+            .find(|question| question.identifier == identifier)
+        else {
+            return false;
+        };
+        self.print_question = PrintQuestion::new(question.clone(), &mut self.rng);
+        self.given_answer = None;
+        self.question_start_time = Instant::now();
+        true
+    }
+
+    /// Starts a new exam of the given part out of all questions of the catalog
+    /// (see the exam module).
+    pub fn create_exam(&mut self, part: ExamPart) -> Exam {
+        Exam::new(part, &self.questions, &mut self.exam_rng)
+    }
+
+    /// Returns whether or not the current question is marked.
+    pub fn is_marked(&self) -> bool {
+        self.learn_states
+            .get(&self.print_question.question.identifier)
+            .is_some_and(|learn_state| learn_state.marked)
+    }
+
+    /// Marks or unmarks the current question and saves the LearnStates.
+    pub fn set_marked(&mut self, is_marked: bool) -> Result<()> {
+        self.learn_states
+            .entry(self.print_question.question.identifier.clone())
+            .or_default()
+            .marked = is_marked;
+        self.save_learning()
+    }
+
+    /// Returns the user's note on the current question, or an empty string if
+    /// there is none.
+    pub fn get_note(&self) -> &str {
+        self.learn_states
+            .get(&self.print_question.question.identifier)
+            .map_or("", |learn_state| learn_state.note.as_str())
+    }
+
+    /// Sets the user's note on the current question (an empty note removes it)
+    /// and saves the LearnStates.
+    pub fn set_note(&mut self, note: &str) -> Result<()> {
+        self.learn_states
+            .entry(self.print_question.question.identifier.clone())
+            .or_default()
+            .note = note.to_string();
+        self.save_learning()
+    }
+
+    /// Returns the user's tags of the current question.
+    pub fn get_tags(&self) -> &[String] {
+        self.learn_states
+            .get(&self.print_question.question.identifier)
+            .map_or(&[], |learn_state| learn_state.tags.as_slice())
+    }
+
+    /// Sets the user's tags of the current question and saves the LearnStates.
+    /// Empty tags and duplicates are left out.
+    pub fn set_tags(&mut self, tags: &[String]) -> Result<()> {
+        let mut new_tags: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !new_tags.iter().any(|new_tag| new_tag == tag) {
+                new_tags.push(tag.to_string());
+            }
+        }
+        self.learn_states
+            .entry(self.print_question.question.identifier.clone())
+            .or_default()
+            .tags = new_tags;
+        let save_result = self.save_learning();
+        let update_result = self.update_learning_dependents();
+        save_result.and(update_result)
+    }
+
+    /// Updates the eligible questions and the statistics after the question
+    /// selection of the Config (e.g. the categories, sections or the active study
+    /// deck) was changed. The Config is not saved.
+    ///
+    /// ### Return value
+    /// * Whether or not the eligible questions were updated; If the Config
+    ///   chooses no question at all, the previous eligible questions are kept
+    pub fn update_eligible_questions(&mut self) -> bool {
+        let eligible_questions =
+            question::get_eligible_questions(&self.questions, &self.config, &self.learn_states);
+        if eligible_questions.is_empty() {
+            return false;
+        }
+        self.eligible_questions = eligible_questions;
+        self.update_statistics();
+        true
+    }
+
+    /// Updates the statistics, e.g. after the scheduler mode was changed.
+    pub fn update_statistics(&mut self) {
+        self.statistics =
+            Statistics::new(&self.eligible_questions, &self.learn_states, &self.config);
+    }
+
+    /// Applies the given exam result to the LearnStates (see ExamResult's
+    /// apply_to_learning()) and saves them.
+    pub fn apply_exam_result(&mut self, exam_result: &ExamResult) -> Result<()> {
+        let apply_result = exam_result.apply_to_learning(&mut self.learn_states, &self.config);
+        let save_result = self.save_learning();
+        let update_result = self.update_learning_dependents();
+        apply_result.and(save_result).and(update_result)
+    }
+
+    /// Saves the LearnStates.
+    pub fn save_learning(&self) -> Result<()> {
+        learning::save_learning(&self.learn_states, &self.config)
+    }
+
+    /// Updates the eligible questions (as the active study deck can depend on the
+    /// questions' tags and bins) and the statistics after the LearnStates changed.
+    /// If the active study deck contains no question anymore, it is deactivated,
+    /// the Config is saved and the user is told so (see take_message()).
+    ///
+    /// ### Return value
+    /// * Nothing, or an FfhError if the Config could not be saved
+    fn update_learning_dependents(&mut self) -> Result<()> {
+        if self.update_eligible_questions() {
+            return Ok(());
+        }
+        let Some(deck_name) = self.config.active_deck.take() else {
+            self.update_statistics();
+            return Ok(());
+        };
+        self.message = Some(get_deck_deactivated_message(&deck_name));
+        if !self.update_eligible_questions() {
+            self.update_statistics();
+        }
+        self.config.save()
+    }
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Returns the message which tells the user that the study deck with the given
+/// name contains no question anymore and was deactivated.
+fn get_deck_deactivated_message(deck_name: &str) -> String {
+    format!(
+        "Der Lernstapel \"{}\" enthält keine Fragen mehr und wurde deaktiviert.",
+        deck_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::deck::Deck;
+
+    /// Returns a session with a single question of class N.
+    fn get_session() -> Session {
+        Session::new(
+            Config::default(),
+            LearnStates::new(),
+            vec![question::test_question("NA101", &[])],
+            Some(1),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn invalid_answer_is_an_error_and_leaves_the_question_unanswered() {
+        let mut session = get_session();
+
+        let result = session.answer(4);
+        assert!(matches!(result, Err(FfhError::InvalidAnswer { index: 4 })));
+        assert_eq!(session.get_given_answer(), None);
+        assert_eq!(session.get_answers_since_start(), 0);
+    }
+
+    #[test]
+    fn emptied_active_deck_is_deactivated_with_a_message() {
+        let dir = std::env::temp_dir().join(format!(
+            "ffh-session-test-{}-{}",
+            std::process::id(),
+            "emptied-deck"
+        ));
+        std::fs::create_dir_all(dir.join("config")).unwrap();
+        std::fs::create_dir_all(dir.join("learning")).unwrap();
+        let mut session = get_session();
+        session.config.data_dir = dir.to_str().unwrap().to_string();
+        // The deck only contains questions which were never answered correctly
+        let mut deck = Deck::new("Neu");
+        deck.max_bin = Some(0);
+        session.config.decks.push(deck);
+        session.config.active_deck = Some(String::from("Neu"));
+        assert!(session.update_eligible_questions());
+
+        let correct_answer = session
+            .get_print_question()
+            .answer_shuffle
+            .iter()
+            .position(|answer| *answer == Answer::A)
+            .unwrap();
+        session.answer(correct_answer).unwrap();
+        assert_eq!(session.config.active_deck, None);
+        assert_eq!(session.get_eligible_questions().len(), 1);
+        assert_eq!(
+            session.take_message(),
+            Some(String::from(
+                "Der Lernstapel \"Neu\" enthält keine Fragen mehr und wurde deaktiviert."
+            ))
+        );
+        assert_eq!(session.take_message(), None);
+        assert_eq!(
+            config::load_config(&session.config.data_dir)
+                .unwrap()
+                .value
+                .active_deck,
+            None
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,476 @@
+//! This module contains Funkfragenhelfer's full-screen terminal UI ("--tui"),
+//! which is built with ratatui and driven entirely by the keyboard.
+//!
+//! The terminal UI drives the same learning session as the GUI's practice view
+//! (see the library's session module) and shows the question, the shuffled
+//! answers, the marking, the session statistics and the learning progress per
+//! bin in separate panes. As in the command-line mode (see the cli module),
+//! formulas are shown as Unicode text and pictures as file paths.
+//!
+//! The keys for answering, continuing, skipping and marking are the Config's
+//! keyboard shortcuts, which are shared with the GUI (see the config module's
+//! KeyBindings); Editing the current question's note with "n" (as with the
+//! command-line mode's "n <Text>") and quitting with "q" or Esc are specific to
+//! the terminal UI. The terminal is restored when the terminal UI ends,
+//! including on a panic.
+
+// IMPORTS SECTION //
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use funkfragenhelfer::{
+    config::KeyBindings,
+    learning::{Answer, Statistics},
+    math,
+    picture::PictureIndex,
+    session::Session,
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use std::io::{self, Stdout};
+use std::panic;
+
+// CONSTANTS SECTION //
+/// Names of the shown (i.e., shuffled) answers, indexed as in PrintQuestion
+const ANSWER_NAMES: [&str; 4] = ["A", "B", "C", "D"];
+
+// PUBLIC FUNCTION SECTION //
+// STRUCT SECTION //
+/// Restores the terminal (i.e., leaves the raw mode and the alternate screen)
+/// when it is dropped, so that the terminal is also restored if the terminal UI
+/// ends with an error or a panic.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+// PUBLIC FUNCTION SECTION //
+/// Runs the terminal UI until the user quits. The terminal is restored
+/// afterwards, even if an error occurred or the terminal UI panicked.
+///
+/// ### Arguments
+/// * session: The learning session
+/// * picture_index: The index of all question and answer picture files
+/// * warnings: Warnings about restored files which are shown to the user
+///
+/// ### Return value
+/// * Nothing, or an io::Error if the terminal could not be used
+pub fn run(
+    session: &mut Session,
+    picture_index: &PictureIndex,
+    warnings: &[String],
+) -> io::Result<()> {
+    // The terminal is restored before a panic message is printed, as the
+    // message would be lost in the alternate screen otherwise
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        restore_terminal();
+        previous_hook(panic_info);
+    }));
+
+    enable_raw_mode()?;
+    let guard = TerminalGuard;
+    let mut stdout = io::stdout();
+    let result = execute!(stdout, EnterAlternateScreen)
+        .and_then(|_| Terminal::new(CrosstermBackend::new(stdout)))
+        .and_then(|mut terminal| run_loop(&mut terminal, session, picture_index, warnings));
+    drop(guard);
+    // The previous panic hook is reinstalled (only the wrapping hook is dropped)
+    drop(panic::take_hook());
+    result
+}
+
+// PRIVATE FUNCTION SECTION //
+/// Leaves the raw mode and the alternate screen. As the terminal is restored
+/// on the way out (e.g. while panicking), errors are ignored.
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen);
+}
+
+/// Returns the crossterm key code of the given key name (as used by the
+/// config module's KeyBindings, e.g. "A", "1", "Enter" or "Space"), or None
+/// if the key is not known. Letters are returned in lowercase.
+fn get_key_code(key_name: &str) -> Option<KeyCode> {
+    let mut chars = key_name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return c
+            .is_ascii_alphanumeric()
+            .then(|| KeyCode::Char(c.to_ascii_lowercase()));
+    }
+    let key_code = match key_name {
+        "Enter" => KeyCode::Enter,
+        "Space" => KeyCode::Char(' '),
+        "Tab" => KeyCode::Tab,
+        "Backspace" => KeyCode::Backspace,
+        "Escape" => KeyCode::Esc,
+        "Insert" => KeyCode::Insert,
+        "Delete" => KeyCode::Delete,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        "ArrowUp" => KeyCode::Up,
+        "ArrowDown" => KeyCode::Down,
+        "ArrowLeft" => KeyCode::Left,
+        "ArrowRight" => KeyCode::Right,
+        _ => {
+            let number = key_name.strip_prefix('F')?.parse().ok()?;
+            KeyCode::F(number)
+        }
+    };
+    Some(key_code)
+}
+
+/// Returns whether or not the given pressed key is one of the given keys
+/// (given by their names, see get_key_code()).
+fn is_key_bound(key_code: KeyCode, key_names: &[String]) -> bool {
+    let key_code = match key_code {
+        KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+        key_code => key_code,
+    };
+    key_names
+        .iter()
+        .any(|key_name| get_key_code(key_name) == Some(key_code))
+}
+
+/// Returns the names of the given known keys for the help line, e.g. "1/A".
+fn get_key_names(key_names: &[String]) -> String {
+    key_names
+        .iter()
+        .filter(|key_name| get_key_code(key_name).is_some())
+        .cloned()
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Returns the help line which lists all keys of the given shortcuts.
+fn get_help(key_bindings: &KeyBindings) -> String {
+    let answer_keys: Vec<String> = (0..ANSWER_NAMES.len())
+        .map(|index| get_key_names(key_bindings.get_answer_keys(index)))
+        .collect();
+    format!(
+        "{}: Antworten | {}: Nächste Frage | {}: Überspringen | {}: Markieren | n: Notiz | \
+         q/Esc: Beenden",
+        answer_keys.join(", "),
+        get_key_names(&key_bindings.next),
+        get_key_names(&key_bindings.skip),
+        get_key_names(&key_bindings.mark)
+    )
+}
+
+/// Returns the index of the shown answer which the given pressed key chooses,
+/// or None if it chooses no answer.
+fn get_answer_index(key_code: KeyCode, key_bindings: &KeyBindings) -> Option<usize> {
+    (0..ANSWER_NAMES.len())
+        .find(|index| is_key_bound(key_code, key_bindings.get_answer_keys(*index)))
+}
+
+/// Draws the terminal UI and handles the pressed keys until the user quits.
+fn run_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    session: &mut Session,
+    picture_index: &PictureIndex,
+    warnings: &[String],
+) -> io::Result<()> {
+    // The last error while saving, which is shown to the user. As in the GUI,
+    // errors while saving are not fatal.
+    let mut last_error: Option<String> = None;
+    // The last message of the session (e.g. about a deactivated study deck)
+    let mut session_message: Option<String> = None;
+    // The edited note of the current question, or None if no note is edited
+    let mut note_input: Option<String> = None;
+    loop {
+        if let Some(message) = session.take_message() {
+            session_message = Some(message);
+        }
+        terminal.draw(|frame| {
+            draw(
+                frame,
+                session,
+                &session_message,
+                &last_error,
+                &note_input,
+                picture_index,
+                warnings,
+            )
+        })?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        // Ignore the release of keys (which is reported under Windows)
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        // While a note is edited, all keys are part of the note, except for Enter
+        // (save the note) and Esc (discard the changes)
+        if let Some(mut input) = note_input.take() {
+            match key.code {
+                KeyCode::Enter => {
+                    if let Err(e) = session.set_note(input.trim()) {
+                        last_error = Some(e.to_string());
+                    }
+                }
+                KeyCode::Esc => {}
+                KeyCode::Backspace => {
+                    input.pop();
+                    note_input = Some(input);
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    note_input = Some(input);
+                }
+                _ => note_input = Some(input),
+            }
+            continue;
+        }
+        let is_answered = session.get_given_answer().is_some();
+        let key_bindings = &session.config.key_bindings;
+        let answer_index = get_answer_index(key.code, key_bindings);
+        let result = if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+            return Ok(());
+        } else if let (Some(answer_index), false) = (answer_index, is_answered) {
+            session.answer(answer_index)
+        } else if (is_answered && is_key_bound(key.code, &key_bindings.next))
+            || (!is_answered && is_key_bound(key.code, &key_bindings.skip))
+        {
+            session.next_question();
+            Ok(())
+        } else if is_key_bound(key.code, &key_bindings.mark) {
+            session.set_marked(!session.is_marked())
+        } else if key.code == KeyCode::Char('n') {
+            note_input = Some(session.get_note().to_string());
+            Ok(())
+        } else {
+            Ok(())
+        };
+        if let Err(e) = result {
+            last_error = Some(e.to_string());
+        }
+    }
+}
+
+/// Draws all panes of the terminal UI.
+fn draw(
+    frame: &mut Frame,
+    session: &Session,
+    session_message: &Option<String>,
+    last_error: &Option<String>,
+    note_input: &Option<String>,
+    picture_index: &PictureIndex,
+    warnings: &[String],
+) {
+    let [message_area, main_area, help_area] = Layout::vertical([
+        Constraint::Length(
+            warnings.len() as u16 + session_message.is_some() as u16 + last_error.is_some() as u16,
+        ),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.size());
+    let [learning_area, statistics_area] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(40)]).areas(main_area);
+    let [question_area, answers_area, marking_area] = Layout::vertical([
+        Constraint::Percentage(40),
+        Constraint::Min(0),
+        Constraint::Length(3),
+    ])
+    .areas(learning_area);
+    let [session_area, progress_area] =
+        Layout::vertical([Constraint::Length(5), Constraint::Min(0)]).areas(statistics_area);
+
+    // Warnings about restored files, the last message of the session and the
+    // last error while saving
+    let mut messages: Vec<Line> = warnings
+        .iter()
+        .map(|warning| Line::from(warning.as_str()))
+        .collect();
+    if let Some(message) = session_message {
+        messages.push(Line::from(message.as_str()));
+    }
+    if let Some(error) = last_error {
+        messages.push(Line::from(format!("Fehler beim Speichern: {}", error)));
+    }
+    frame.render_widget(Paragraph::new(messages).red().bold(), message_area);
+
+    draw_question(frame, question_area, session, picture_index);
+    draw_answers(frame, answers_area, session, picture_index);
+
+    // While a note is edited, the note replaces the marking
+    if let Some(input) = note_input {
+        frame.render_widget(
+            Paragraph::new(format!("{}_", input))
+                .block(Block::bordered().title("Notiz (Enter: Speichern, Esc: Abbrechen)")),
+            marking_area,
+        );
+    } else {
+        let marking = if session.is_marked() {
+            "[X] Markiert"
+        } else {
+            "[ ] Nicht markiert"
+        };
+        frame.render_widget(
+            Paragraph::new(marking).block(Block::bordered().title(format!(
+                "Markierung ({})",
+                get_key_names(&session.config.key_bindings.mark)
+            ))),
+            marking_area,
+        );
+    }
+
+    let session_lines = vec![
+        Line::from(format!(
+            "Korrekt beantwortete Fragen: {}, {} %",
+            session.get_correct_answers_since_start(),
+            (session.get_correct_answers_since_start() * 100)
+                .checked_div(session.get_answers_since_start())
+                .unwrap_or(0)
+        )),
+        Line::from(format!(
+            "Insgesamt beantwortete Fragen: {}",
+            session.get_answers_since_start()
+        )),
+    ];
+    frame.render_widget(
+        Paragraph::new(session_lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title("Aktuelle Session")),
+        session_area,
+    );
+    draw_progress(frame, progress_area, session.get_statistics());
+
+    frame.render_widget(
+        Paragraph::new(get_help(&session.config.key_bindings)).dark_gray(),
+        help_area,
+    );
+}
+
+/// Draws the pane of the current question, including its picture's file path.
+fn draw_question(frame: &mut Frame, area: Rect, session: &Session, picture_index: &PictureIndex) {
+    let question = &session.get_print_question().question;
+    let mut text = Text::from(math::to_unicode(&question.question));
+    if !question.picture_question.is_empty() {
+        text.push_line("");
+        text.push_line(get_picture_line(picture_index, &question.picture_question));
+    }
+    frame.render_widget(
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(format!("Frage {}", question.identifier))),
+        area,
+    );
+}
+
+/// Draws the pane of the shuffled answers. After the question was answered,
+/// the correct answer is highlighted in green and a wrong given answer in red.
+fn draw_answers(frame: &mut Frame, area: Rect, session: &Session, picture_index: &PictureIndex) {
+    let print_question = session.get_print_question();
+    let mut text = Text::default();
+    for (index, answer_name) in ANSWER_NAMES.iter().enumerate() {
+        let mut style = Style::default();
+        if let Some(given_answer) = session.get_given_answer() {
+            if print_question.answer_shuffle[index] == Answer::A {
+                style = style.fg(Color::Green).add_modifier(Modifier::BOLD);
+            } else if index == given_answer {
+                style = style.fg(Color::Red).add_modifier(Modifier::BOLD);
+            }
+        }
+        let answer_text = math::to_unicode(&print_question.get_shuffled_answer(index));
+        text.push_line(Line::styled(
+            format!("{}: {}", answer_name, answer_text),
+            style,
+        ));
+        let answer_picture = print_question.get_shuffled_picture(index);
+        if !answer_picture.is_empty() {
+            text.push_line(format!(
+                "   {}",
+                get_picture_line(picture_index, &answer_picture)
+            ));
+        }
+    }
+    if session.get_given_answer().is_some() && !session.get_note().is_empty() {
+        text.push_line("");
+        text.push_line(Line::from(format!("Notiz: {}", session.get_note())).italic());
+    }
+
+    let key_bindings = &session.config.key_bindings;
+    let next_keys = get_key_names(&key_bindings.next);
+    let title = match session.is_given_answer_correct() {
+        None => format!(
+            "Antworten ({})",
+            (0..ANSWER_NAMES.len())
+                .map(|index| get_key_names(key_bindings.get_answer_keys(index)))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+        Some(true) => format!("Korrekt! ({}: Nächste Frage)", next_keys),
+        Some(false) => format!(
+            "Falsch! Richtige Antwort ist {:?} ({}: Nächste Frage)",
+            print_question.get_correct_answer(),
+            next_keys
+        ),
+    };
+    frame.render_widget(
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(title)),
+        area,
+    );
+}
+
+/// Draws the pane of the learning progress, including the number of questions per bin.
+fn draw_progress(frame: &mut Frame, area: Rect, statistics: &Statistics) {
+    let mut lines = vec![
+        Line::from(format!(
+            "Korrekt beantwortet: {}, {} %",
+            statistics.correct_answers,
+            (statistics.correct_answers * 100)
+                .checked_div(statistics.questions)
+                .unwrap_or(0)
+        )),
+        Line::from(format!(
+            "Noch nicht korrekt: {}, {} %",
+            statistics.no_correct_answers,
+            (statistics.no_correct_answers * 100)
+                .checked_div(statistics.questions)
+                .unwrap_or(0)
+        )),
+        Line::from(format!("Fragen insgesamt: {}", statistics.questions)),
+        Line::from(format!("Heute fällig: {}", statistics.due_today)),
+        Line::from(""),
+        Line::from("Fragen pro Lerntopf:".bold()),
+    ];
+    let mut bins = statistics.count_per_bin.keys().collect::<Vec<_>>();
+    bins.sort();
+    for bin in bins {
+        lines.push(Line::from(format!(
+            "Lerntopf '{}': {}",
+            bin, statistics.count_per_bin[bin]
+        )));
+    }
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title("Lernfortschritt")),
+        area,
+    );
+}
+
+/// Returns the line with the file path of the given picture, or a note if its file is missing.
+fn get_picture_line(picture_index: &PictureIndex, picture: &str) -> String {
+    match picture_index.get_path(picture) {
+        Some(path) => format!("Bild: {}", path),
+        None => format!("Bild '{}' fehlt", picture),
+    }
+}